@@ -1,5 +1,8 @@
 // src/data/loader.rs
 use super::templates::*;
+use crate::templates::item_templates::{ItemTemplate, ItemTemplateRegistry};
+use crate::templates::loot_tables::{LootTable, LootTableRegistry};
+use crate::templates::npc_templates::{NPCTemplate, NPCTemplateRegistry};
 use std::fs;
 use std::path::Path;
 use walkdir::WalkDir;
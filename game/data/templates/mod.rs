@@ -1,10 +1,4 @@
 // src/data/templates/mod.rs
 pub mod monster_templates;
-pub mod item_templates;
-pub mod npc_templates;
-pub mod loot_tables;
 
 pub use monster_templates::*;
-pub use item_templates::*;
-pub use npc_templates::*;
-pub use loot_tables::*;
\ No newline at end of file
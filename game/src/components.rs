@@ -1,32 +1,49 @@
 // src/components.rs
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Component)]
 pub struct Player;
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Monster {
     pub ai_type: AIType,
 }
 
+/// Someone the player can talk to rather than fight. One who has been attacked
+/// also gets a `Monster` component and fights like one.
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct Npc {
+    pub template_id: String,
+}
+
 #[derive(Component)]
 pub struct Name(pub String);
 
-#[derive(Component, Clone, PartialEq, Copy)]
+#[derive(Component, Clone, PartialEq, Copy, Serialize, Deserialize)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
     pub level: i32,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Health {
     pub current: i32,
     pub max: i32,
 }
 
-#[derive(Component)]
+/// Spent on spells. The maximum comes from intelligence.
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct Mana {
+    pub current: i32,
+    pub max: i32,
+    /// Turn count mana last came back on
+    pub last_regen: u32,
+}
+
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Stats {
     pub strength: i32,
     pub dexterity: i32,
@@ -34,7 +51,7 @@ pub struct Stats {
     pub constitution: i32,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct CombatStats {
     pub damage: i32,
     pub defense: i32,
@@ -42,18 +59,268 @@ pub struct CombatStats {
     pub evasion: i32,
 }
 
+/// Character level, which scales loot and (for monsters) stats
+#[derive(Component, Clone, Copy, Serialize, Deserialize)]
+pub struct Level(pub i32);
+
+/// Experience earned towards the next level; resets on every level up
+#[derive(Component, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Experience {
+    pub current: u32,
+}
+
+/// Improves loot rolls; extra rolls are granted according to each table's `per_luck`
+#[derive(Component, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Luck(pub f32);
+
+/// What an entity can currently see, recomputed by the map plugin whenever it moves
+#[derive(Component)]
+pub struct Viewshed {
+    pub visible_tiles: HashSet<(i32, i32)>,
+    pub range: i32,
+    /// Forces a recompute even if the entity hasn't moved
+    pub dirty: bool,
+}
+
+impl Viewshed {
+    pub fn new(range: i32) -> Self {
+        Self {
+            visible_tiles: HashSet::new(),
+            range,
+            dirty: true,
+        }
+    }
+
+    pub fn can_see(&self, position: &Position) -> bool {
+        self.visible_tiles.contains(&(position.x, position.y))
+    }
+}
+
 #[derive(Component)]
 pub struct Inventory {
     pub items: Vec<Entity>,
     pub capacity: usize,
 }
 
-#[derive(Component)]
+impl Inventory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            items: Vec::new(),
+            capacity,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.items.len() >= self.capacity
+    }
+}
+
+/// The slots an item can be equipped in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EquipSlot {
+    Weapon,
+    Armor,
+    Shield,
+}
+
+/// Items the player is wearing or wielding. Equipped items are held here rather
+/// than in the `Inventory`, so they don't take up pack space.
+#[derive(Component, Default)]
+pub struct Equipment {
+    pub weapon: Option<Entity>,
+    pub armor: Option<Entity>,
+    pub shield: Option<Entity>,
+}
+
+impl Equipment {
+    pub fn slot(&self, slot: EquipSlot) -> Option<Entity> {
+        match slot {
+            EquipSlot::Weapon => self.weapon,
+            EquipSlot::Armor => self.armor,
+            EquipSlot::Shield => self.shield,
+        }
+    }
+
+    pub fn slot_mut(&mut self, slot: EquipSlot) -> &mut Option<Entity> {
+        match slot {
+            EquipSlot::Weapon => &mut self.weapon,
+            EquipSlot::Armor => &mut self.armor,
+            EquipSlot::Shield => &mut self.shield,
+        }
+    }
+
+    pub fn equipped(&self) -> impl Iterator<Item = Entity> {
+        [self.weapon, self.armor, self.shield].into_iter().flatten()
+    }
+}
+
+/// Gold carried by the player. Coins don't take up inventory space.
+#[derive(Component, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Purse {
+    pub gold: u32,
+}
+
+/// The player's standing with each faction, by faction id. Unknown factions are neutral.
+#[derive(Component, Clone, Default, Serialize, Deserialize)]
+pub struct Reputation(pub HashMap<String, i32>);
+
+impl Reputation {
+    pub fn with(&self, faction: &str) -> i32 {
+        self.0.get(faction).copied().unwrap_or(0)
+    }
+}
+
+/// What the player has said and done in conversations, set and checked by dialogue trees
+#[derive(Component, Clone, Default, Serialize, Deserialize)]
+pub struct DialogueFlags(pub HashSet<String>);
+
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Item {
     pub item_type: ItemType,
+    /// How many of the item this entity stands for; at most the template's stack size
     pub stack_size: u32,
 }
 
+/// Uses left in the top item of a stack of multi-use consumables. An item without
+/// it hasn't been touched yet and has all of its template's charges.
+#[derive(Component, Clone, Copy, Serialize, Deserialize)]
+pub struct ItemCharges(pub u32);
+
+/// The turn each consumable, by template id, can next be used on
+#[derive(Component, Clone, Default, Serialize, Deserialize)]
+pub struct ItemCooldowns(pub HashMap<String, u32>);
+
+/// Poison doses beyond this only refresh the poison's duration
+const MAX_POISON_STACKS: u32 = 3;
+
+/// What a status effect does while it lasts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StatusKind {
+    /// Damage every turn, for every stack
+    Poison { damage: i32 },
+    /// Healing every turn
+    Regeneration { amount: i32 },
+    /// Raises a stat
+    StatBuff { stat: String, amount: i32 },
+    /// Lowers a stat by `amount` until it wears off or is lifted
+    Curse { stat: String, amount: i32 },
+    /// Reveals monsters within `radius` tiles, even out of sight
+    DetectMonsters { radius: i32 },
+}
+
+impl StatusKind {
+    /// Shown next to health in the stats and combat panels
+    pub fn icon(&self) -> char {
+        match self {
+            StatusKind::Poison { .. } => '☠',
+            StatusKind::Regeneration { .. } => '♥',
+            StatusKind::StatBuff { .. } => '▲',
+            StatusKind::Curse { .. } => '▼',
+            StatusKind::DetectMonsters { .. } => '◎',
+        }
+    }
+
+    /// The stat it changes and by how much, for buffs and curses
+    pub fn stat_change(&self) -> Option<(&str, i32)> {
+        match self {
+            StatusKind::StatBuff { stat, amount } => Some((stat, *amount)),
+            StatusKind::Curse { stat, amount } => Some((stat, -amount)),
+            _ => None,
+        }
+    }
+}
+
+/// A timed condition. Turns are counted on `GameWorld::turn_count`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// What inflicted or granted it, e.g. "Goblin Archer" or "Elixir of Strength"
+    pub source: String,
+    /// Turn count at which it wears off
+    pub expires_at: u32,
+    /// Last turn its per-turn effect was applied for
+    pub last_tick: u32,
+    /// Doses of poison taken; always 1 for anything else
+    pub stacks: u32,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, source: String, duration: u32, turn: u32) -> Self {
+        Self { kind, source, expires_at: turn + duration, last_tick: turn, stacks: 1 }
+    }
+
+    pub fn remaining(&self, turn: u32) -> u32 {
+        self.expires_at.saturating_sub(turn)
+    }
+}
+
+/// How a new status effect combined with those already active
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusApplied {
+    Added,
+    /// Poison got another dose
+    Stacked(u32),
+    /// An effect like it was already active and now lasts longer
+    Refreshed,
+}
+
+#[derive(Component, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+impl StatusEffects {
+    /// Adds an effect. Poison stacks up to a limit, regeneration keeps the strongest
+    /// rate, and buffs and curses refresh when the same source applies them again
+    /// but stack with those from anywhere else. Whatever combines with an active
+    /// effect extends it to the longer of the two durations.
+    pub fn apply(&mut self, effect: StatusEffect) -> StatusApplied {
+        let existing = self.0.iter_mut().find(|active| match (&active.kind, &effect.kind) {
+            (StatusKind::Poison { .. }, StatusKind::Poison { .. }) => true,
+            (StatusKind::Regeneration { .. }, StatusKind::Regeneration { .. }) => true,
+            (StatusKind::DetectMonsters { .. }, StatusKind::DetectMonsters { .. }) => true,
+            (StatusKind::StatBuff { stat: a, .. }, StatusKind::StatBuff { stat: b, .. })
+            | (StatusKind::Curse { stat: a, .. }, StatusKind::Curse { stat: b, .. }) => a == b && active.source == effect.source,
+            _ => false,
+        });
+        let Some(active) = existing else {
+            self.0.push(effect);
+            return StatusApplied::Added;
+        };
+
+        active.expires_at = active.expires_at.max(effect.expires_at);
+        match (&mut active.kind, effect.kind) {
+            (StatusKind::Poison { damage }, StatusKind::Poison { damage: new_damage }) => {
+                *damage = (*damage).max(new_damage);
+                if active.stacks < MAX_POISON_STACKS {
+                    active.stacks += 1;
+                    return StatusApplied::Stacked(active.stacks);
+                }
+            }
+            (StatusKind::Regeneration { amount }, StatusKind::Regeneration { amount: new_amount }) => {
+                *amount = (*amount).max(new_amount);
+            }
+            (kind, new_kind) => *kind = new_kind,
+        }
+        StatusApplied::Refreshed
+    }
+
+    /// How far monsters can be sensed through walls, if at all
+    pub fn detection_radius(&self) -> Option<i32> {
+        self.0.iter()
+            .filter_map(|effect| match effect.kind {
+                StatusKind::DetectMonsters { radius } => Some(radius),
+                _ => None,
+            })
+            .max()
+    }
+
+    /// Removes every effect matching `predicate`, returning how many went
+    pub fn remove_where(&mut self, predicate: impl Fn(&StatusKind) -> bool) -> usize {
+        let before = self.0.len();
+        self.0.retain(|effect| !predicate(&effect.kind));
+        before - self.0.len()
+    }
+}
+
 // --- Helper Enums ---
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AIType {
@@ -62,10 +329,12 @@ pub enum AIType {
     Passive,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ItemType {
     Weapon { damage: i32 },
     Armor { defense: i32 },
     Potion { heal_amount: i32 },
     Gold { amount: u32 },
+    /// Keys, trinkets and quest items that do nothing on their own
+    Misc,
 }
\ No newline at end of file
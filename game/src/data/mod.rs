@@ -1,7 +1,14 @@
 use bevy::prelude::*;
 
 // Re-export templates for easier access
+pub use crate::templates::dialogue_templates::DialogueRegistry;
+pub use crate::templates::faction_templates::FactionRegistry;
+pub use crate::templates::item_templates::ItemTemplateRegistry;
+pub use crate::templates::loot_tables::LootTableRegistry;
 pub use crate::templates::monster_templates::MonsterTemplateRegistry;
+pub use crate::templates::npc_templates::NPCTemplateRegistry;
+pub use crate::templates::quest_templates::QuestTemplateRegistry;
+pub use crate::templates::spell_templates::SpellTemplateRegistry;
 
 // We can keep the GameData resource for future use, but the key part is the plugin.
 #[derive(Resource, Default)]
@@ -14,9 +21,16 @@ impl Plugin for DataPlugin {
         app
             .init_resource::<GameData>()
             .init_resource::<MonsterTemplateRegistry>()
+            .init_resource::<ItemTemplateRegistry>()
+            .init_resource::<LootTableRegistry>()
+            .init_resource::<SpellTemplateRegistry>()
+            .init_resource::<NPCTemplateRegistry>()
+            .init_resource::<DialogueRegistry>()
+            .init_resource::<QuestTemplateRegistry>()
+            .init_resource::<FactionRegistry>()
             // This is the crucial part: load the data before the main app starts up.
             // PreStartup runs before any OnEnter states, guaranteeing the data is ready.
-            .add_systems(PreStartup, load_monster_data);
+            .add_systems(PreStartup, (load_monster_data, load_item_data, load_loot_tables, load_spell_data, load_npc_data, load_dialogue_data, load_quest_data, load_faction_data));
     }
 }
 
@@ -51,4 +65,123 @@ fn load_monster_data(
     // add more files here
     // let undead_data = include_str!("../../../data/monsters/undead.json");
     // monster_registry.load_from_json(undead_data).unwrap();
-}
\ No newline at end of file
+}
+
+/// System that loads weapon, armor, consumable and misc templates into the item registry.
+fn load_item_data(mut item_registry: ResMut<ItemTemplateRegistry>) {
+    let item_files = [
+        ("swords.json", include_str!("../../data/items/weapons/swords.json")),
+        ("potions.json", include_str!("../../data/items/consumables/potions.json")),
+        ("scrolls.json", include_str!("../../data/items/consumables/scrolls.json")),
+        ("trinkets.json", include_str!("../../data/items/misc/trinkets.json")),
+    ];
+
+    for (file_name, item_data) in item_files {
+        if let Err(e) = item_registry.load_from_json(item_data) {
+            error!("Failed to load item data from {}: {}", file_name, e);
+        }
+    }
+
+    info!("Successfully loaded {} item templates", item_registry.count());
+}
+
+/// System that loads the loot tables monsters refer to through `loot_table_id`,
+/// the ones merchants stock their shops from and the ones quests reward.
+fn load_loot_tables(mut loot_registry: ResMut<LootTableRegistry>) {
+    let loot_files = [
+        ("monster_loot.json", include_str!("../../data/loot_tables/monster_loot.json")),
+        ("merchant_stock.json", include_str!("../../data/loot_tables/merchant_stock.json")),
+        ("quest_rewards.json", include_str!("../../data/loot_tables/quest_rewards.json")),
+    ];
+
+    for (file_name, loot_data) in loot_files {
+        if let Err(e) = loot_registry.load_from_json(loot_data) {
+            error!("Failed to load loot tables from {}: {}", file_name, e);
+        }
+    }
+
+    info!("Successfully loaded {} loot tables", loot_registry.count());
+}
+
+/// System that loads the spells the player can learn.
+fn load_spell_data(mut spell_registry: ResMut<SpellTemplateRegistry>) {
+    let spell_files = [
+        ("evocation.json", include_str!("../../data/spells/evocation.json")),
+        ("utility.json", include_str!("../../data/spells/utility.json")),
+    ];
+
+    for (file_name, spell_data) in spell_files {
+        if let Err(e) = spell_registry.load_from_json(spell_data) {
+            error!("Failed to load spell data from {}: {}", file_name, e);
+        }
+    }
+
+    info!("Successfully loaded {} spells", spell_registry.count());
+}
+
+/// System that loads the townsfolk and the places they live.
+fn load_npc_data(mut npc_registry: ResMut<NPCTemplateRegistry>) {
+    let npc_files = [
+        ("merchants.json", include_str!("../../data/npcs/merchants.json")),
+        ("townsfolk.json", include_str!("../../data/npcs/townsfolk.json")),
+    ];
+
+    for (file_name, npc_data) in npc_files {
+        if let Err(e) = npc_registry.load_from_json(npc_data) {
+            error!("Failed to load NPC data from {}: {}", file_name, e);
+        }
+    }
+
+    if let Err(e) = npc_registry.load_locations_from_json(include_str!("../../data/npcs/locations.json")) {
+        error!("Failed to load NPC locations from locations.json: {}", e);
+    }
+
+    info!("Successfully loaded {} NPC templates", npc_registry.count());
+}
+
+/// System that loads the hand-written conversations. NPCs without one talk from
+/// their dialogue personality instead.
+fn load_dialogue_data(mut dialogue_registry: ResMut<DialogueRegistry>) {
+    let dialogue_files = [
+        ("merchants.json", include_str!("../../data/dialogue/merchants.json")),
+        ("townsfolk.json", include_str!("../../data/dialogue/townsfolk.json")),
+    ];
+
+    for (file_name, dialogue_data) in dialogue_files {
+        if let Err(e) = dialogue_registry.load_from_json(dialogue_data) {
+            error!("Failed to load dialogue from {}: {}", file_name, e);
+        }
+    }
+
+    info!("Successfully loaded {} dialogue trees", dialogue_registry.count());
+}
+
+/// System that loads the quests NPCs hand out.
+fn load_quest_data(mut quest_registry: ResMut<QuestTemplateRegistry>) {
+    let quest_files = [
+        ("town.json", include_str!("../../data/quests/town.json")),
+    ];
+
+    for (file_name, quest_data) in quest_files {
+        if let Err(e) = quest_registry.load_from_json(quest_data) {
+            error!("Failed to load quests from {}: {}", file_name, e);
+        }
+    }
+
+    info!("Successfully loaded {} quests", quest_registry.count());
+}
+
+/// System that loads the factions NPCs and monsters belong to.
+fn load_faction_data(mut faction_registry: ResMut<FactionRegistry>) {
+    let faction_files = [
+        ("factions.json", include_str!("../../data/factions/factions.json")),
+    ];
+
+    for (file_name, faction_data) in faction_files {
+        if let Err(e) = faction_registry.load_from_json(faction_data) {
+            error!("Failed to load factions from {}: {}", file_name, e);
+        }
+    }
+
+    info!("Successfully loaded {} factions", faction_registry.count());
+}
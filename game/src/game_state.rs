@@ -1,7 +1,8 @@
 // src/game_state.rs
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, States)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, States, Serialize, Deserialize)]
 pub enum GameState {
     #[default]
     MainMenu,
@@ -1,5 +1,4 @@
 // src/main.rs
-
 use bevy::prelude::*;
 use bevy_egui::EguiPlugin;
 
@@ -9,27 +8,51 @@ mod game_state;
 mod plugins;
 mod resources;
 mod setup;
+mod systems;
 mod templates;
 
 use data::DataPlugin;
 use game_state::GameState;
-use plugins::{ui, player, monster, combat};
-use resources::MessageLog;
+use plugins::{ui, player, progression, inventory, equipment, magic, monster, npc, combat, dungeon, map, save, shop, dialogue, quest, faction};
+use resources::{GameRng, GameWorld, MessageLog, NewGameSettings};
 
 fn main() {
     App::new()
-        .add_plugins(DefaultPlugins)
+        // The save plugin closes the window itself once the run has been autosaved
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            close_when_requested: false,
+            ..default()
+        }))
         .add_plugins(EguiPlugin)
         .init_state::<GameState>()
         .init_resource::<MessageLog>()
+        .init_resource::<GameWorld>()
+        .init_resource::<GameRng>()
+        .init_resource::<NewGameSettings>()
         // Add the data plugin which loads all JSON data
         .add_plugins(DataPlugin)
+        .add_plugins(systems::SystemsPlugin)
         .add_plugins((
             setup::SetupPlugin,
+            map::MapPlugin,
+            dungeon::DungeonPlugin,
             ui::UiPlugin,
             player::PlayerPlugin,
+            progression::ProgressionPlugin,
+            inventory::InventoryPlugin,
+            equipment::EquipmentPlugin,
+            magic::MagicPlugin,
             monster::MonsterPlugin,
+            // Plugin tuples top out at fifteen, so the townsfolk get one of their own
+            (
+                npc::NpcPlugin,
+                shop::ShopPlugin,
+                dialogue::DialoguePlugin,
+                quest::QuestPlugin,
+                faction::FactionPlugin,
+            ),
             combat::CombatPlugin,
+            save::SavePlugin,
         ))
         .run();
 }
\ No newline at end of file
@@ -1,9 +1,44 @@
-// src/plugins/combat.rs - FIXED IMPORTS
+// src/plugins/combat.rs
 
 use bevy::prelude::*;
-use crate::components::{CombatStats, Health, Name, Player, Monster};
+use crate::components::{AIType, CombatStats, Equipment, Health, Item, ItemType, Level, Luck, Name, Npc, Player, Monster, Position, Stats, Viewshed};
 use crate::game_state::GameState;
-use crate::resources::{MessageLog, TurnState};
+use crate::resources::{GameRng, GameWorld, MessageLog};
+use crate::plugins::map::Map;
+use crate::plugins::combat_rules::{roll_attack, AttackOutcome, Combatant, CombatRuleset, WieldedWeapon};
+use crate::plugins::faction::faction_of;
+use crate::plugins::progression::{kill_experience, stat_modifier, GainExperienceEvent};
+use crate::systems::loot_system::DropLootEvent;
+use crate::systems::status_effects::ApplyStatusEvent;
+use crate::templates::item_templates::{DamageType, ItemTemplate, ItemTemplateRef, ItemTemplateRegistry, ModifierType};
+use crate::templates::monster_templates::{MonsterTemplateRef, MonsterTemplateRegistry};
+use crate::templates::npc_templates::NPCTemplateRegistry;
+use rand::Rng;
+use std::collections::HashSet;
+
+/// Monsters this close to the player that can see them join a fight when it starts
+const ENCOUNTER_RADIUS: i32 = 6;
+
+/// Keys that pick the first nine hostiles in turn order as the player's target
+const TARGET_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3,
+    KeyCode::Digit4, KeyCode::Digit5, KeyCode::Digit6,
+    KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+];
+
+const COMBAT_ACTION_KEYS: [(KeyCode, CombatAction); 3] = [
+    (KeyCode::KeyD, CombatAction::Defend),
+    (KeyCode::KeyW, CombatAction::Wait),
+    (KeyCode::KeyF, CombatAction::Flee),
+];
+
+/// Defensive monsters brace themselves some of the time once below this fraction of health...
+const DEFEND_HEALTH_RATIO: f32 = 0.5;
+const DEFEND_CHANCE: f64 = 0.5;
+/// ...and try to run below this one
+const FLEE_HEALTH_RATIO: f32 = 0.3;
+
+const ESCAPE_DIRECTIONS: [(i32, i32); 4] = [(0, 1), (0, -1), (-1, 0), (1, 0)];
 
 pub struct CombatPlugin;
 
@@ -12,20 +47,32 @@ impl Plugin for CombatPlugin {
         app
             .add_event::<AttackEvent>()
             .add_event::<StartCombatEvent>()
+            .add_event::<CombatActionEvent>()
+            .add_event::<MonsterSlainEvent>()
+            .add_event::<PlayerKilledMonsterEvent>()
             .init_resource::<CurrentCombat>()
+            .init_resource::<CombatRuleset>()
             .add_systems(OnEnter(GameState::InCombat), setup_combat)
             .add_systems(OnExit(GameState::InCombat), teardown_combat)
             .add_systems(
                 Update,
                 (
                     handle_combat_start,
+                    expire_defending,
                     combat_input_system,
+                    count_player_turns,
                     process_attacks,
+                    process_combat_actions,
                     monster_ai_system,
                     check_combat_end,
                 )
                 .chain()
                 .run_if(in_state(GameState::InCombat))
+            )
+            // Monsters die to spells and poison outside fights too
+            .add_systems(
+                Update,
+                handle_monster_deaths.run_if(in_state(GameState::Exploring).or_else(in_state(GameState::InCombat)))
             );
     }
 }
@@ -36,23 +83,110 @@ pub struct AttackEvent {
     pub defender: Entity,
 }
 
+/// Anything a combatant can do on their turn besides attacking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CombatAction {
+    /// Raise defense until the actor's next turn
+    Defend,
+    /// Let the turn pass
+    Wait,
+    /// Try to break away. The player leaves the fight; a monster leaves it to the rest.
+    Flee,
+}
+
+#[derive(Event)]
+pub struct CombatActionEvent {
+    pub actor: Entity,
+    pub action: CombatAction,
+}
+
+/// Sent when a monster's health runs out, by whatever brought it down. The
+/// killer earns the experience and their luck decides the loot.
+#[derive(Event)]
+pub struct MonsterSlainEvent {
+    pub monster: Entity,
+    pub killer: Option<Entity>,
+}
+
+/// Sent for every monster that falls to the player, provoked NPCs included,
+/// for whatever keeps count of kills
+#[derive(Event)]
+pub struct PlayerKilledMonsterEvent {
+    /// `None` for NPCs, which aren't made from monster templates
+    pub template_id: Option<String>,
+    pub faction: Option<String>,
+}
+
+/// Marks a combatant who defended on their last turn
+#[derive(Component)]
+pub struct Defending;
+
+/// Pulls a monster into the fight, starting one if none is under way
 #[derive(Event)]
 pub struct StartCombatEvent {
     pub monster: Entity,
 }
 
+/// The fight in progress: everyone taking part in initiative order, and whose turn it is
 #[derive(Resource, Default)]
 pub struct CurrentCombat {
-    pub monster_entity: Option<Entity>,
+    /// The player and every monster in the fight, highest initiative first
+    pub participants: Vec<Entity>,
+    /// Index into `participants` of whoever acts next
+    pub turn: usize,
+    /// The monster the player's attacks go to
+    pub target: Option<Entity>,
+    /// Whoever struck last. Kept after the fight so the game over screen can name the killer.
+    pub last_attacker: Option<Entity>,
 }
 
-fn setup_combat(
-    mut commands: Commands, 
-    mut message_log: ResMut<MessageLog>,
-) {
-    commands.insert_resource(TurnState::PlayerTurn);
+impl CurrentCombat {
+    /// Whoever's turn it is
+    pub fn current(&self) -> Option<Entity> {
+        self.participants.get(self.turn).copied()
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.participants.contains(&entity)
+    }
+
+    /// Everyone in the fight except the player, in initiative order
+    pub fn hostiles(&self, player: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.participants.iter().copied().filter(move |&entity| entity != player)
+    }
+
+    pub fn first_hostile(&self, player: Entity) -> Option<Entity> {
+        self.hostiles(player).next()
+    }
+
+    /// Passes the turn to the next participant
+    pub fn end_turn(&mut self) {
+        if !self.participants.is_empty() {
+            self.turn = (self.turn + 1) % self.participants.len();
+        }
+    }
+
+    /// Takes someone out of the fight without disturbing whose turn it is
+    pub fn remove(&mut self, entity: Entity) {
+        let Some(index) = self.participants.iter().position(|&participant| participant == entity) else {
+            return;
+        };
+        self.participants.remove(index);
+        if index < self.turn {
+            self.turn -= 1;
+        }
+        if self.turn >= self.participants.len() {
+            self.turn = 0;
+        }
+        if self.target == Some(entity) {
+            self.target = None;
+        }
+    }
+}
+
+fn setup_combat(mut message_log: ResMut<MessageLog>) {
     message_log.add(
-        "Combat begins! Press 'A' to attack.".to_string(),
+        "Combat begins! Press 1-9 to choose a target, 'A' to attack, 'D' to defend, 'W' to wait, 'F' to flee, 'I' to use an item or 'C' to cast a spell.".to_string(),
         Color::ORANGE_RED,
     );
 }
@@ -60,84 +194,285 @@ fn setup_combat(
 fn teardown_combat(
     mut commands: Commands,
     mut current_combat: ResMut<CurrentCombat>,
+    defending: Query<Entity, With<Defending>>,
 ) {
-    commands.remove_resource::<TurnState>();
-    current_combat.monster_entity = None;
+    for entity in defending.iter() {
+        commands.entity(entity).remove::<Defending>();
+    }
+    *current_combat = CurrentCombat {
+        last_attacker: current_combat.last_attacker,
+        ..default()
+    };
+}
+
+/// Initiative decides the order everyone acts in: a d20 plus the dexterity modifier
+fn roll_initiative<R: Rng + ?Sized>(stats: &Stats, rng: &mut R) -> i32 {
+    rng.gen_range(1..=20) + stat_modifier(stats.dexterity)
 }
 
+/// Adds monsters to the fight. A new fight also draws in every hostile nearby that
+/// can see the player, so packs attack together.
 fn handle_combat_start(
     mut events: EventReader<StartCombatEvent>,
     mut current_combat: ResMut<CurrentCombat>,
+    player_query: Query<(Entity, &Position), With<Player>>,
+    monster_query: Query<(Entity, &Monster, &Position, &Viewshed)>,
+    combatant_query: Query<(&Stats, &Name)>,
+    mut rng: ResMut<GameRng>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let mut joining: Vec<Entity> = events.read().map(|event| event.monster).collect();
+    if joining.is_empty() {
+        return;
+    }
+    let Ok((player, player_pos)) = player_query.get_single() else {
+        return;
+    };
+
+    let fresh = current_combat.participants.is_empty();
+    if fresh {
+        for (entity, monster, position, viewshed) in monster_query.iter() {
+            let nearby = position.level == player_pos.level
+                && (position.x - player_pos.x).abs() + (position.y - player_pos.y).abs() <= ENCOUNTER_RADIUS;
+            let hostile = !matches!(monster.ai_type, AIType::Passive);
+            if nearby && hostile && viewshed.can_see(player_pos) && !joining.contains(&entity) {
+                joining.push(entity);
+            }
+        }
+        joining.push(player);
+    }
+
+    let mut rolled: Vec<(Entity, i32)> = Vec::new();
+    for entity in joining {
+        if current_combat.contains(entity) || rolled.iter().any(|&(joined, _)| joined == entity) {
+            continue;
+        }
+        let Ok((stats, _)) = combatant_query.get(entity) else {
+            continue;
+        };
+        rolled.push((entity, roll_initiative(stats, &mut *rng)));
+    }
+    rolled.sort_by_key(|&(_, initiative)| std::cmp::Reverse(initiative));
+
+    let name_of = |entity: Entity| combatant_query.get(entity).map_or("someone".to_string(), |(_, name)| name.0.clone());
+    if fresh {
+        let order: Vec<String> = rolled.iter().map(|&(entity, _)| name_of(entity)).collect();
+        message_log.add(format!("Turn order: {}", order.join(", ")), Color::GRAY);
+        current_combat.turn = 0;
+    } else {
+        // Latecomers act after everyone already in the fight
+        for &(entity, _) in rolled.iter() {
+            message_log.add(format!("{} joins the fight!", name_of(entity)), Color::ORANGE_RED);
+        }
+    }
+    current_combat.participants.extend(rolled.into_iter().map(|(entity, _)| entity));
+
+    if current_combat.target.is_none() {
+        current_combat.target = current_combat.first_hostile(player);
+    }
+}
+
+/// A defensive stance lasts until its owner's next turn comes round
+fn expire_defending(
+    mut commands: Commands,
+    current_combat: Res<CurrentCombat>,
+    defending: Query<(), With<Defending>>,
 ) {
-    for event in events.read() {
-        current_combat.monster_entity = Some(event.monster);
+    if let Some(actor) = current_combat.current().filter(|&actor| defending.contains(actor)) {
+        commands.entity(actor).remove::<Defending>();
     }
 }
 
 fn combat_input_system(
     keyboard: Res<ButtonInput<KeyCode>>,
-    turn_state: Res<TurnState>,
     mut attack_events: EventWriter<AttackEvent>,
+    mut action_events: EventWriter<CombatActionEvent>,
     player_query: Query<Entity, With<Player>>,
-    current_combat: Res<CurrentCombat>,
+    name_query: Query<&Name>,
+    mut current_combat: ResMut<CurrentCombat>,
     mut message_log: ResMut<MessageLog>,
 ) {
-    if *turn_state != TurnState::PlayerTurn {
+    let Ok(player_entity) = player_query.get_single() else {
+        return;
+    };
+    if current_combat.current() != Some(player_entity) {
         return;
     }
 
+    for (index, key) in TARGET_KEYS.iter().enumerate() {
+        if !keyboard.just_pressed(*key) {
+            continue;
+        }
+        let hostile = current_combat.hostiles(player_entity).nth(index);
+        if let Some(hostile) = hostile {
+            current_combat.target = Some(hostile);
+            if let Ok(name) = name_query.get(hostile) {
+                message_log.add(format!("You turn to face {}.", name.0), Color::WHITE);
+            }
+        }
+    }
+
     if keyboard.just_pressed(KeyCode::KeyA) {
-        let Ok(player_entity) = player_query.get_single() else {
-            message_log.add("No player found!".to_string(), Color::RED);
-            return;
-        };
-        
-        let Some(monster_entity) = current_combat.monster_entity else {
+        let Some(target) = current_combat.target else {
             message_log.add("No monster in combat!".to_string(), Color::RED);
             return;
         };
         
         attack_events.send(AttackEvent {
             attacker: player_entity,
-            defender: monster_entity,
+            defender: target,
         });
+    } else if let Some(action) = COMBAT_ACTION_KEYS.iter()
+        .find(|(key, _)| keyboard.just_pressed(*key))
+        .map(|&(_, action)| action)
+    {
+        action_events.send(CombatActionEvent { actor: player_entity, action });
     }
 }
 
-fn process_attacks(
-    mut commands: Commands,
+/// Looks up the weapon in a combatant's weapon slot, if it has one
+pub fn wielded_weapon(
+    equipment: Option<&Equipment>,
+    weapon_query: &Query<(&Item, &Name, &ItemTemplateRef)>,
+    registry: &ItemTemplateRegistry,
+) -> Option<WieldedWeapon> {
+    let (item, name, template_ref) = weapon_query.get(equipment?.weapon?).ok()?;
+    match (&item.item_type, registry.get(&template_ref.0)) {
+        (ItemType::Weapon { damage }, Some(ItemTemplate::Weapon(template))) => Some(WieldedWeapon {
+            name: name.0.clone(),
+            average_damage: *damage,
+            range: template.damage.clone(),
+            attack_speed: template.attack_speed,
+            crit_bonus: template.modifiers.iter()
+                .filter(|modifier| modifier.stat == "critical_chance" && matches!(modifier.modifier_type, ModifierType::Flat))
+                .map(|modifier| modifier.value.round() as i32)
+                .sum(),
+        }),
+        _ => None,
+    }
+}
+
+pub fn damage_verb(damage_type: DamageType) -> &'static str {
+    match damage_type {
+        DamageType::Physical => "hits",
+        DamageType::Fire => "burns",
+        DamageType::Cold => "freezes",
+        DamageType::Lightning => "shocks",
+        DamageType::Poison => "poisons",
+        DamageType::Holy => "smites",
+        DamageType::Shadow => "withers",
+    }
+}
+
+/// Points out when a monster shrugged off or suffered extra from a blow
+pub fn resistance_message(name: &str, damage_type: DamageType, multiplier: f32) -> Option<(String, Color)> {
+    if multiplier == 0.0 {
+        Some((format!("{} is immune to {} damage!", name, damage_type.name()), Color::GRAY))
+    } else if multiplier > 1.0 {
+        Some((format!("{} is weak to {} damage!", name, damage_type.name()), Color::ORANGE))
+    } else if multiplier < 1.0 {
+        Some((format!("{} resists {} damage.", name, damage_type.name()), Color::GRAY))
+    } else {
+        None
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_attacks(
     mut attack_events: EventReader<AttackEvent>,
-    mut combatants: Query<(&mut Health, &CombatStats, &Name)>,
+    mut combatants: Query<(&mut Health, &Stats, &CombatStats, &Name, Has<Defending>)>,
     player_query: Query<(), With<Player>>,
+    template_query: Query<&MonsterTemplateRef>,
+    equipment_query: Query<&Equipment>,
+    weapon_query: Query<(&Item, &Name, &ItemTemplateRef)>,
+    templates: Res<MonsterTemplateRegistry>,
+    item_registry: Res<ItemTemplateRegistry>,
+    rules: Res<CombatRuleset>,
     mut message_log: ResMut<MessageLog>,
-    mut turn_state: ResMut<TurnState>,
+    mut current_combat: ResMut<CurrentCombat>,
+    mut rng: ResMut<GameRng>,
+    mut slain_events: EventWriter<MonsterSlainEvent>,
+    mut status_events: EventWriter<ApplyStatusEvent>,
 ) {
     for event in attack_events.read() {
-        let Ok([(mut _attacker_health, attacker_stats, attacker_name), (mut defender_health, defender_stats, defender_name)]) =
-            combatants.get_many_mut([event.attacker, event.defender]) else { 
+        // Only whoever's turn it is gets to swing
+        if current_combat.current() != Some(event.attacker) {
+            continue;
+        }
+        current_combat.last_attacker = Some(event.attacker);
+
+        let Ok([(_, attacker_stats, attacker_combat, attacker_name, _), (mut defender_health, defender_stats, defender_combat, defender_name, defending)]) =
+            combatants.get_many_mut([event.attacker, event.defender]) else {
+                // A blow at someone who's gone still spends the turn, or the fight
+                // would wait on it forever. Whoever vanished leaves the fight.
+                if !combatants.contains(event.attacker) {
+                    // The next in line moves up into the attacker's turn
+                    current_combat.remove(event.attacker);
+                } else {
+                    if !combatants.contains(event.defender) {
+                        current_combat.remove(event.defender);
+                    }
+                    current_combat.end_turn();
+                }
                 continue;
             };
 
-        let hit_chance = attacker_stats.accuracy - defender_stats.evasion;
-        let hit_roll = rand::random::<i32>() % 100;
+        let weapon = wielded_weapon(equipment_query.get(event.attacker).ok(), &weapon_query, &item_registry);
+        let defender_weapon = wielded_weapon(equipment_query.get(event.defender).ok(), &weapon_query, &item_registry);
+        let damage_type = weapon.as_ref().map_or(DamageType::Physical, |weapon| weapon.range.damage_type);
+
+        // Monsters shrug off or suffer extra from some kinds of damage
+        let multiplier = template_query.get(event.defender).ok()
+            .and_then(|template_ref| templates.get(&template_ref.0))
+            .map_or(1.0, |template| template.damage_multiplier(damage_type));
 
-        if hit_roll < hit_chance {
-            let damage = (attacker_stats.damage - defender_stats.defense).max(1);
+        let attacker = Combatant { stats: attacker_stats, combat: attacker_combat, weapon: weapon.as_ref(), defending: false };
+        let defender = Combatant { stats: defender_stats, combat: defender_combat, weapon: defender_weapon.as_ref(), defending };
+
+        if let AttackOutcome::Hit { damage, critical } = roll_attack(&**rules, &attacker, &defender, multiplier, &mut *rng) {
             defender_health.current -= damage;
 
-            message_log.add(
-                format!("{} hits {} for {} damage!", attacker_name.0, defender_name.0, damage),
-                Color::RED,
-            );
+            if critical {
+                message_log.add("A critical hit!".to_string(), Color::YELLOW);
+            }
+
+            let message = match &weapon {
+                Some(weapon) if weapon.range.damage_type != DamageType::Physical => format!(
+                    "{} {} {} for {} {} damage!",
+                    weapon.name,
+                    damage_verb(weapon.range.damage_type),
+                    defender_name.0,
+                    damage,
+                    weapon.range.damage_type.name(),
+                ),
+                Some(weapon) => format!("{} hits {} with {} for {} damage!", attacker_name.0, defender_name.0, weapon.name, damage),
+                None => format!("{} hits {} for {} damage!", attacker_name.0, defender_name.0, damage),
+            };
+            message_log.add(message, Color::RED);
+            if let Some((message, color)) = resistance_message(&defender_name.0, damage_type, multiplier) {
+                message_log.add(message, color);
+            }
 
             if defender_health.current <= 0 {
                 message_log.add(
                     format!("{} has been slain!", defender_name.0),
                     Color::DARK_GRAY,
                 );
-                // Only despawn the defender if it's not the player
-                if player_query.get(event.defender).is_err() {
-                    commands.entity(event.defender).despawn();
+                // The player's death ends the fight instead
+                if !player_query.contains(event.defender) {
+                    // Out of the turn order at once, so it can't strike back before it's cleared away
+                    current_combat.remove(event.defender);
+                    slain_events.send(MonsterSlainEvent { monster: event.defender, killer: Some(event.attacker) });
+                }
+            } else if let Some(template) = template_query.get(event.attacker).ok().and_then(|template_ref| templates.get(&template_ref.0)) {
+                // Venomous bites and hexes only take hold on a hit that didn't kill
+                for on_hit in template.on_hit.iter().filter(|on_hit| rng.gen_bool(on_hit.chance.clamp(0.0, 1.0))) {
+                    status_events.send(ApplyStatusEvent {
+                        target: event.defender,
+                        source: attacker_name.0.clone(),
+                        kind: on_hit.status.clone(),
+                        duration: on_hit.duration,
+                    });
                 }
             }
         } else {
@@ -147,71 +482,286 @@ fn process_attacks(
             );
         }
 
-        // Switch turns
-        match *turn_state {
-            TurnState::PlayerTurn => *turn_state = TurnState::MonsterTurn,
-            TurnState::MonsterTurn => *turn_state = TurnState::PlayerTurn,
+        current_combat.end_turn();
+    }
+}
+
+/// Hands out experience and loot for a fallen monster and takes it out of the world
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn handle_monster_deaths(
+    mut commands: Commands,
+    mut slain_events: EventReader<MonsterSlainEvent>,
+    player_query: Query<(Option<&Luck>, Option<&Level>), With<Player>>,
+    monster_query: Query<(&Position, &Level, Option<&MonsterTemplateRef>, Option<&Npc>), With<Monster>>,
+    templates: Res<MonsterTemplateRegistry>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    mut current_combat: ResMut<CurrentCombat>,
+    mut loot_events: EventWriter<DropLootEvent>,
+    mut xp_events: EventWriter<GainExperienceEvent>,
+    mut kill_events: EventWriter<PlayerKilledMonsterEvent>,
+) {
+    // A monster can be reported slain more than once before it's despawned
+    let mut handled = HashSet::new();
+    for event in slain_events.read() {
+        if !handled.insert(event.monster) {
+            continue;
+        }
+        let Ok((position, level, template_ref, npc)) = monster_query.get(event.monster) else {
+            continue;
+        };
+        let killer = event.killer.and_then(|killer| player_query.get(killer).ok());
+
+        if killer.is_some() {
+            kill_events.send(PlayerKilledMonsterEvent {
+                template_id: template_ref.map(|template_ref| template_ref.0.clone()),
+                faction: faction_of(template_ref, npc, &templates, &npc_registry).map(str::to_string),
+            });
+        }
+
+        // NPCs who were provoked have no template, so nothing to earn or drop
+        if let Some(template) = template_ref.and_then(|template_ref| templates.get(&template_ref.0)) {
+            let (luck, player_level) = match killer {
+                Some((luck, player_level)) => (luck.map_or(0.0, |luck| luck.0), player_level.map_or(1, |level| level.0)),
+                None => (0.0, 1),
+            };
+            // Only the player earns experience from kills
+            if killer.is_some() {
+                xp_events.send(GainExperienceEvent {
+                    amount: kill_experience(template.experience_reward, level.0, player_level),
+                });
+            }
+            // Whatever the monster carried is left behind, rolled at its level with the killer's luck
+            if let Some(loot_table_id) = template.loot_table_id.clone() {
+                loot_events.send(DropLootEvent {
+                    loot_table_id,
+                    position: *position,
+                    level: level.0.max(0) as u32,
+                    luck,
+                });
+            }
         }
+
+        current_combat.remove(event.monster);
+        commands.entity(event.monster).despawn();
     }
 }
 
-pub fn monster_ai_system(
-    turn_state: Res<TurnState>,
-    mut attack_events: EventWriter<AttackEvent>,
+/// Keeps the game's turn count running through fights, so buffs and cooldowns
+/// wear off there too. Each attack or action on the player's turn is one turn.
+fn count_player_turns(
+    mut attack_events: EventReader<AttackEvent>,
+    mut action_events: EventReader<CombatActionEvent>,
     player_query: Query<Entity, With<Player>>,
     current_combat: Res<CurrentCombat>,
-    monster_query: Query<&Monster>,
+    mut game_world: ResMut<GameWorld>,
 ) {
-    if *turn_state != TurnState::MonsterTurn {
+    let Ok(player) = player_query.get_single() else {
         return;
+    };
+    // Only the first of them gets its turn; the rest are ignored
+    let acted = attack_events.read().filter(|event| event.attacker == player).count()
+        + action_events.read().filter(|event| event.actor == player).count();
+    if acted > 0 && current_combat.current() == Some(player) {
+        game_world.turn_count += 1;
     }
-    
-    let Some(monster_entity) = current_combat.monster_entity else {
+}
+
+/// Resolves defending, waiting and fleeing
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn process_combat_actions(
+    mut commands: Commands,
+    mut action_events: EventReader<CombatActionEvent>,
+    mut current_combat: ResMut<CurrentCombat>,
+    combatants: Query<(&Stats, &CombatStats, &Name)>,
+    mut positions: Query<(Entity, &mut Position), Or<(With<Player>, With<Monster>, With<Npc>)>>,
+    player_query: Query<Entity, With<Player>>,
+    map: Res<Map>,
+    rules: Res<CombatRuleset>,
+    mut rng: ResMut<GameRng>,
+    mut message_log: ResMut<MessageLog>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+
+    for event in action_events.read() {
+        if current_combat.current() != Some(event.actor) {
+            continue;
+        }
+        let Ok((runner_stats, runner_combat, name)) = combatants.get(event.actor) else {
+            continue;
+        };
+
+        match event.action {
+            CombatAction::Wait => {
+                message_log.add(format!("{} waits.", name.0), Color::GRAY);
+            }
+            CombatAction::Defend => {
+                commands.entity(event.actor).insert(Defending);
+                message_log.add(format!("{} braces for the next blow.", name.0), Color::CYAN);
+            }
+            CombatAction::Flee => {
+                // The player runs from everyone; a monster only has the player to get away from
+                let pursuers: Vec<Entity> = if event.actor == player {
+                    current_combat.hostiles(player).collect()
+                } else {
+                    vec![player]
+                };
+                // The quickest pursuer is the one that matters
+                let Some((pursuer_stats, pursuer_combat)) = pursuers.iter()
+                    .filter_map(|&pursuer| combatants.get(pursuer).ok())
+                    .map(|(stats, combat, _)| (stats, combat))
+                    .max_by_key(|(stats, _)| stats.dexterity)
+                else {
+                    continue;
+                };
+                let runner = Combatant { stats: runner_stats, combat: runner_combat, weapon: None, defending: false };
+                let pursuer = Combatant { stats: pursuer_stats, combat: pursuer_combat, weapon: None, defending: false };
+
+                let Ok((_, from)) = positions.get(event.actor) else {
+                    continue;
+                };
+                let from = *from;
+                let threats: Vec<Position> = pursuers.iter()
+                    .filter_map(|&pursuer| positions.get(pursuer).ok())
+                    .map(|(_, position)| *position)
+                    .collect();
+                let occupied: Vec<Position> = positions.iter().map(|(_, position)| *position).collect();
+
+                let Some(escape) = escape_tile(from, &threats, &occupied, &map) else {
+                    message_log.add(format!("{} has nowhere to run!", name.0), Color::ORANGE);
+                    current_combat.end_turn();
+                    continue;
+                };
+                if rng.gen_range(0..100) >= rules.flee_chance(&runner, &pursuer) {
+                    message_log.add(format!("{} tries to flee but can't get away!", name.0), Color::ORANGE);
+                    current_combat.end_turn();
+                    continue;
+                }
+
+                if let Ok((_, mut position)) = positions.get_mut(event.actor) {
+                    *position = escape;
+                }
+                commands.entity(event.actor).remove::<Defending>();
+                if event.actor == player {
+                    message_log.add("You flee from the fight!".to_string(), Color::LIME_GREEN);
+                    next_state.set(GameState::Exploring);
+                } else {
+                    message_log.add(format!("{} flees!", name.0), Color::ORANGE);
+                    // Leaving the fight hands the turn on by itself
+                    current_combat.remove(event.actor);
+                }
+                continue;
+            }
+        }
+
+        current_combat.end_turn();
+    }
+}
+
+/// The open neighbouring tile that puts the most distance between `from` and the
+/// nearest threat, as long as it's further than standing still
+fn escape_tile(from: Position, threats: &[Position], occupied: &[Position], map: &Map) -> Option<Position> {
+    let nearest_threat = |tile: &Position| threats.iter()
+        .map(|threat| (threat.x - tile.x).abs() + (threat.y - tile.y).abs())
+        .min()
+        .unwrap_or(i32::MAX);
+
+    ESCAPE_DIRECTIONS.iter()
+        .map(|(dx, dy)| Position { x: from.x + dx, y: from.y + dy, level: from.level })
+        .filter(|tile| map.is_walkable(tile) && !occupied.contains(tile))
+        .filter(|tile| nearest_threat(tile) > nearest_threat(&from))
+        .max_by_key(nearest_threat)
+}
+
+/// Monsters act when their turn in the order comes round. Aggressive ones always
+/// attack; defensive ones brace when hurt and run when badly hurt; passive ones
+/// only want to get away.
+pub fn monster_ai_system(
+    mut attack_events: EventWriter<AttackEvent>,
+    mut action_events: EventWriter<CombatActionEvent>,
+    player_query: Query<Entity, With<Player>>,
+    current_combat: Res<CurrentCombat>,
+    monster_query: Query<(&Monster, &Health)>,
+    mut rng: ResMut<GameRng>,
+) {
+    let Some(actor) = current_combat.current() else {
         return;
     };
     
-    // Check if the monster still exists
-    if monster_query.get(monster_entity).is_err() {
+    // The player's turn, or a monster that's no longer around
+    let Ok((monster, health)) = monster_query.get(actor) else {
         return;
-    }
+    };
     
     let Ok(player_entity) = player_query.get_single() else {
         return;
     };
-    
-    attack_events.send(AttackEvent {
-        attacker: monster_entity,
-        defender: player_entity,
-    });
+
+    let health_ratio = health.current as f32 / health.max as f32;
+    let action = match monster.ai_type {
+        AIType::Aggressive => None,
+        AIType::Defensive if health_ratio < FLEE_HEALTH_RATIO => Some(CombatAction::Flee),
+        AIType::Defensive if health_ratio < DEFEND_HEALTH_RATIO && rng.gen_bool(DEFEND_CHANCE) => Some(CombatAction::Defend),
+        AIType::Defensive => None,
+        AIType::Passive => Some(CombatAction::Flee),
+    };
+
+    match action {
+        Some(action) => {
+            action_events.send(CombatActionEvent { actor, action });
+        }
+        None => {
+            attack_events.send(AttackEvent {
+                attacker: actor,
+                defender: player_entity,
+            });
+        }
+    }
 }
 
+/// Ends the fight once every hostile is gone, or the player is
 fn check_combat_end(
-    current_combat: Res<CurrentCombat>,
+    mut current_combat: ResMut<CurrentCombat>,
     monster_query: Query<&Monster>,
-    player_query: Query<&Health, With<Player>>,
+    player_query: Query<(Entity, &Health), With<Player>>,
     mut game_state: ResMut<NextState<GameState>>,
     mut message_log: ResMut<MessageLog>,
 ) {
-    // Check if the current combat monster is dead
-    if let Some(monster_entity) = current_combat.monster_entity {
-        if monster_query.get(monster_entity).is_err() {
-            message_log.add(
-                "You are victorious! You can move again.".to_string(),
-                Color::LIME_GREEN,
-            );
-            game_state.set(GameState::Exploring);
-            return;
-        }
+    let Ok((player_entity, player_health)) = player_query.get_single() else {
+        return;
+    };
+
+    // Drop anyone who has vanished from the world since their last turn
+    let gone: Vec<Entity> = current_combat.hostiles(player_entity)
+        .filter(|&entity| !monster_query.contains(entity))
+        .collect();
+    for entity in gone {
+        current_combat.remove(entity);
     }
-    
+
     // Check if player is dead
-    if let Ok(player_health) = player_query.get_single() {
-        if player_health.current <= 0 {
-            message_log.add(
-                "You have been slain! Game Over.".to_string(),
-                Color::RED,
-            );
-            game_state.set(GameState::GameOver);
-        }
+    if player_health.current <= 0 {
+        message_log.add(
+            "You have been slain! Game Over.".to_string(),
+            Color::RED,
+        );
+        game_state.set(GameState::GameOver);
+        return;
     }
-}
\ No newline at end of file
+
+    if current_combat.first_hostile(player_entity).is_none() {
+        message_log.add(
+            "No enemies remain. You can move again.".to_string(),
+            Color::LIME_GREEN,
+        );
+        game_state.set(GameState::Exploring);
+        return;
+    }
+
+    // Keep the player's attacks pointed at someone still standing
+    if current_combat.target.is_none() {
+        current_combat.target = current_combat.first_hostile(player_entity);
+    }
+}
@@ -0,0 +1,209 @@
+// src/plugins/combat_rules.rs
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::components::{CombatStats, Stats};
+use crate::plugins::progression::stat_modifier;
+use crate::templates::item_templates::DamageRange;
+
+/// Chance every hit has of being a critical before any bonuses, in percent
+const BASE_CRIT_CHANCE: i32 = 5;
+const MAX_CRIT_CHANCE: i32 = 50;
+const BASE_CRIT_MULTIPLIER: f32 = 1.5;
+/// Extra critical damage per point of strength modifier
+const CRIT_MULTIPLIER_PER_STRENGTH: f32 = 0.05;
+/// Accuracy gained for every point of attack speed above 1.0
+const ACCURACY_PER_ATTACK_SPEED: f32 = 20.0;
+/// Every attack has at least this chance to land, and to miss
+const MIN_HIT_CHANCE: i32 = 5;
+const MAX_HIT_CHANCE: i32 = 95;
+/// Defending adds this much to defense, or doubles it if that's more
+const MIN_DEFEND_BONUS: i32 = 2;
+/// Chance of getting away from an equally nimble opponent, in percent
+const BASE_FLEE_CHANCE: i32 = 50;
+/// Flee chance gained for every point of dexterity over the pursuer's
+const FLEE_CHANCE_PER_DEXTERITY: i32 = 5;
+const MIN_FLEE_CHANCE: i32 = 10;
+const MAX_FLEE_CHANCE: i32 = 90;
+
+/// The weapon a blow is struck with: its name, the damage it adds on average,
+/// the range and element it really rolls in, and how quick and keen it is
+pub struct WieldedWeapon {
+    pub name: String,
+    pub average_damage: i32,
+    pub range: DamageRange,
+    pub attack_speed: f32,
+    pub crit_bonus: i32,
+}
+
+/// Everything the rules get to see about one side of an attack
+pub struct Combatant<'a> {
+    pub stats: &'a Stats,
+    pub combat: &'a CombatStats,
+    pub weapon: Option<&'a WieldedWeapon>,
+    /// Braced for blows since their last turn
+    pub defending: bool,
+}
+
+impl Combatant<'_> {
+    /// Defense including the bonus for defending
+    pub fn defense(&self) -> i32 {
+        if self.defending {
+            self.combat.defense + self.combat.defense.max(MIN_DEFEND_BONUS)
+        } else {
+            self.combat.defense
+        }
+    }
+}
+
+/// The formulas that decide how attacks land. Swap the `CombatRuleset` resource to
+/// change how combat plays without touching the systems that run it.
+pub trait CombatRules: Send + Sync + 'static {
+    /// Shown in the combat debug panel
+    fn name(&self) -> &'static str;
+
+    /// Chance in percent that an attack connects
+    fn hit_chance(&self, attacker: &Combatant, defender: &Combatant) -> i32;
+
+    /// Chance in percent that a hit is a critical
+    fn crit_chance(&self, attacker: &Combatant, defender: &Combatant) -> i32;
+
+    /// How much a critical multiplies the blow's raw damage
+    fn crit_multiplier(&self, attacker: &Combatant) -> f32;
+
+    /// Damage dealt by a blow of `raw_damage` once the defender's armour has its say
+    fn damage(&self, attacker: &Combatant, defender: &Combatant, raw_damage: i32) -> i32;
+
+    /// Chance in percent that `runner` gets away from `pursuer`
+    fn flee_chance(&self, runner: &Combatant, pursuer: &Combatant) -> i32 {
+        let dexterity_edge = runner.stats.dexterity - pursuer.stats.dexterity;
+        (BASE_FLEE_CHANCE + dexterity_edge * FLEE_CHANCE_PER_DEXTERITY).clamp(MIN_FLEE_CHANCE, MAX_FLEE_CHANCE)
+    }
+}
+
+/// The original formulas: accuracy against evasion, damage against defense, no criticals
+pub struct ClassicRules;
+
+impl CombatRules for ClassicRules {
+    fn name(&self) -> &'static str {
+        "Classic"
+    }
+
+    fn hit_chance(&self, attacker: &Combatant, defender: &Combatant) -> i32 {
+        attacker.combat.accuracy - defender.combat.evasion
+    }
+
+    fn crit_chance(&self, _attacker: &Combatant, _defender: &Combatant) -> i32 {
+        0
+    }
+
+    fn crit_multiplier(&self, _attacker: &Combatant) -> f32 {
+        1.0
+    }
+
+    fn damage(&self, _attacker: &Combatant, defender: &Combatant, raw_damage: i32) -> i32 {
+        (raw_damage - defender.defense()).max(1)
+    }
+}
+
+/// The default rules. Quick weapons land more often, dexterity and keen weapons
+/// find criticals, and strength makes them hurt. Nothing is ever certain to hit
+/// or to miss.
+pub struct StatDrivenRules;
+
+impl CombatRules for StatDrivenRules {
+    fn name(&self) -> &'static str {
+        "Stat-driven"
+    }
+
+    fn hit_chance(&self, attacker: &Combatant, defender: &Combatant) -> i32 {
+        let speed_bonus = attacker.weapon
+            .map_or(0.0, |weapon| (weapon.attack_speed - 1.0) * ACCURACY_PER_ATTACK_SPEED)
+            .round() as i32;
+        (attacker.combat.accuracy - defender.combat.evasion + speed_bonus).clamp(MIN_HIT_CHANCE, MAX_HIT_CHANCE)
+    }
+
+    fn crit_chance(&self, attacker: &Combatant, _defender: &Combatant) -> i32 {
+        let weapon_bonus = attacker.weapon.map_or(0, |weapon| weapon.crit_bonus);
+        (BASE_CRIT_CHANCE + stat_modifier(attacker.stats.dexterity) + weapon_bonus).clamp(0, MAX_CRIT_CHANCE)
+    }
+
+    fn crit_multiplier(&self, attacker: &Combatant) -> f32 {
+        (BASE_CRIT_MULTIPLIER + stat_modifier(attacker.stats.strength) as f32 * CRIT_MULTIPLIER_PER_STRENGTH).max(1.0)
+    }
+
+    fn damage(&self, _attacker: &Combatant, defender: &Combatant, raw_damage: i32) -> i32 {
+        (raw_damage - defender.defense()).max(1)
+    }
+}
+
+/// The rules combat is currently played by
+#[derive(Resource)]
+pub struct CombatRuleset(pub Box<dyn CombatRules>);
+
+impl Default for CombatRuleset {
+    fn default() -> Self {
+        Self(Box::new(StatDrivenRules))
+    }
+}
+
+impl std::ops::Deref for CombatRuleset {
+    type Target = dyn CombatRules;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+/// How a single attack turned out
+pub enum AttackOutcome {
+    Miss,
+    Hit { damage: i32, critical: bool },
+}
+
+/// Rolls a whole attack: whether it lands, whether it crits and how much it hurts.
+/// `multiplier` is the defender's weakness or resistance to the blow's damage type.
+pub fn roll_attack<R: Rng + ?Sized>(
+    rules: &dyn CombatRules,
+    attacker: &Combatant,
+    defender: &Combatant,
+    multiplier: f32,
+    rng: &mut R,
+) -> AttackOutcome {
+    if rng.gen_range(0..100) >= rules.hit_chance(attacker, defender) {
+        return AttackOutcome::Miss;
+    }
+
+    let critical = rng.gen_range(0..100) < rules.crit_chance(attacker, defender);
+    let mut raw_damage = roll_raw_damage(attacker, rng);
+    if critical {
+        raw_damage = (raw_damage as f32 * rules.crit_multiplier(attacker)).round() as i32;
+    }
+
+    AttackOutcome::Hit {
+        damage: apply_multiplier(rules.damage(attacker, defender, raw_damage), multiplier),
+        critical,
+    }
+}
+
+/// Damage an ordinary hit does on average, for previews
+pub fn expected_damage(rules: &dyn CombatRules, attacker: &Combatant, defender: &Combatant, multiplier: f32) -> i32 {
+    apply_multiplier(rules.damage(attacker, defender, attacker.combat.damage), multiplier)
+}
+
+/// Raw damage of a blow before armour. The attacker's damage already includes the
+/// weapon's average, so the roll only adds the weapon's spread around it.
+fn roll_raw_damage<R: Rng + ?Sized>(attacker: &Combatant, rng: &mut R) -> i32 {
+    match attacker.weapon {
+        Some(weapon) => {
+            let roll = rng.gen_range(weapon.range.min..=weapon.range.max.max(weapon.range.min));
+            attacker.combat.damage - weapon.average_damage + roll
+        }
+        None => attacker.combat.damage,
+    }
+}
+
+fn apply_multiplier(damage: i32, multiplier: f32) -> i32 {
+    (damage as f32 * multiplier).round() as i32
+}
@@ -0,0 +1,332 @@
+// src/plugins/dialogue.rs
+
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::components::{DialogueFlags, Health, Inventory, Item, Level, Mana, Monster, Npc, Player, Purse, Reputation};
+use crate::game_state::GameState;
+use crate::plugins::faction::{ReputationChangeEvent, HOSTILE_REPUTATION};
+use crate::plugins::npc::NpcDialog;
+use crate::plugins::shop::OpenShopEvent;
+use crate::resources::{GameRng, MessageLog};
+use crate::systems::item_spawning::spawn_carried_item;
+use crate::templates::dialogue_templates::{DialogueCondition, DialogueEffect, DialogueRegistry, DialogueResponse};
+use crate::templates::item_templates::{ItemTemplateRef, ItemTemplateRegistry};
+use crate::templates::npc_templates::{DialoguePersonality, NPCTemplateRegistry};
+
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<ChooseResponseEvent>()
+            .add_event::<AskRumorEvent>()
+            .init_resource::<Conversation>()
+            .add_systems(
+                Update,
+                (start_conversation_system, choose_response_system, ask_rumor_system)
+                    .chain()
+                    .run_if(in_state(GameState::Exploring))
+            );
+    }
+}
+
+/// Sent when the player picks one of the replies on offer, by its index in the current node
+#[derive(Event)]
+pub struct ChooseResponseEvent {
+    pub index: usize,
+}
+
+/// Asks the NPC the player is talking to for the local gossip
+#[derive(Event)]
+pub struct AskRumorEvent;
+
+/// Where the conversation with the NPC in the dialog window has got to
+#[derive(Resource, Default)]
+pub struct Conversation {
+    pub npc: Option<Entity>,
+    /// Node of the NPC's dialogue tree being spoken; `None` for NPCs without one
+    pub node: Option<String>,
+    /// What the NPC said last
+    pub line: String,
+}
+
+/// What dialogue conditions are checked against
+pub struct DialogueContext<'a> {
+    pub level: i32,
+    pub gold: u32,
+    /// Template IDs of everything in the player's pack
+    pub carried: Vec<String>,
+    pub reputation: &'a Reputation,
+    pub flags: &'a DialogueFlags,
+}
+
+impl DialogueContext<'_> {
+    pub fn allows(&self, condition: &DialogueCondition) -> bool {
+        match condition {
+            DialogueCondition::MinLevel(level) => self.level >= *level,
+            DialogueCondition::HasGold(gold) => self.gold >= *gold,
+            DialogueCondition::HasItem(item_id) => self.carried.iter().any(|carried| carried == item_id),
+            DialogueCondition::Flag(flag) => self.flags.0.contains(flag),
+            DialogueCondition::NotFlag(flag) => !self.flags.0.contains(flag),
+            DialogueCondition::MinReputation { faction, amount } => self.reputation.with(faction) >= *amount,
+            DialogueCondition::MaxReputation { faction, amount } => self.reputation.with(faction) <= *amount,
+        }
+    }
+
+    pub fn allows_response(&self, response: &DialogueResponse) -> bool {
+        response.conditions.iter().all(|condition| self.allows(condition))
+    }
+}
+
+/// What NPCs without a dialogue tree say to enemies of their faction
+const COLD_GREETINGS: [&str; 3] = [
+    "I've heard about you. Say what you want and go.",
+    "You've got some nerve showing your face here.",
+    "We've nothing to say to each other.",
+];
+
+/// An opener for how the NPC speaks, then how they take to company
+pub fn generate_greeting<R: Rng + ?Sized>(personality: &DialoguePersonality, rng: &mut R) -> String {
+    let openers: &[&str] = match personality.speaking_style.as_str() {
+        "formal" => &["Good day to you.", "Greetings, traveller."],
+        "archaic" => &["Hail, wanderer.", "Well met, stranger."],
+        "casual" => &["Hey there!", "Oh, hello!"],
+        "folksy" => &["Well now, come in out of the cold.", "Evening, friend."],
+        "direct" => &["You.", "Yes?"],
+        _ => &["Hello."],
+    };
+    let moods: &[&str] = match personality.tone.as_str() {
+        "friendly" => &["Always good to see a new face.", "What can I do for you?"],
+        "warm" => &["Sit a while, you look like you've earned it.", "You're welcome here."],
+        "gruff" => &["Make it quick.", "What do you want?"],
+        "mysterious" => &["I wondered when you would come.", "The stones said you'd be by."],
+        _ => &["What brings you here?"],
+    };
+
+    let opener = openers.choose(rng).copied().unwrap_or("Hello.");
+    let mood = moods.choose(rng).copied().unwrap_or("What brings you here?");
+    format!("{} {}", opener, mood)
+}
+
+/// A bit of gossip about something the NPC cares about or knows, told the way
+/// their personality would tell it
+pub fn generate_rumor<R: Rng + ?Sized>(personality: &DialoguePersonality, rng: &mut R) -> String {
+    let topics: Vec<&String> = personality.interests.iter()
+        .chain(personality.knowledge_areas.iter())
+        .collect();
+    let Some(topic) = topics.choose(rng) else {
+        return "I don't hear much of anything, I'm afraid.".to_string();
+    };
+
+    let intro = personality.personality_traits.iter()
+        .filter_map(|personality_trait| match personality_trait.as_str() {
+            "helpful" => Some("Here's something that might help you. "),
+            "talkative" => Some("Oh, have you heard? "),
+            "honest" => Some("I'll be straight with you. "),
+            "proud" => Some("Not many know this, but I do. "),
+            "impatient" => Some("Quickly, then. "),
+            "wistful" => Some("Takes me back, this does. "),
+            "protective" => Some("Mind yourself down there. "),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .choose(rng)
+        .copied()
+        .unwrap_or("");
+    let rumor = match rng.gen_range(0..4) {
+        0 => format!("Folk have been saying strange things about {} lately.", topic),
+        1 => format!("The goblins below have been restless. Something to do with {}, if you ask me.", topic),
+        2 => format!("You didn't hear it from me, but there's more to {} than people think.", topic),
+        _ => format!("Ask me about {} some time. I could talk all day.", topic),
+    };
+    format!("{}{}", intro, rumor)
+}
+
+/// Opens on the NPC's dialogue tree if they have one, or a greeting in their
+/// own voice otherwise, one that's short with enemies of their faction. Keeps in
+/// step with the dialog window opening and closing.
+fn start_conversation_system(
+    dialog: Res<NpcDialog>,
+    mut conversation: ResMut<Conversation>,
+    npc_query: Query<&Npc, Without<Monster>>,
+    player_query: Query<&Reputation, With<Player>>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    dialogues: Res<DialogueRegistry>,
+    mut rng: ResMut<GameRng>,
+) {
+    if conversation.npc == dialog.npc {
+        return;
+    }
+    *conversation = Conversation::default();
+    let Some(entity) = dialog.npc else {
+        return;
+    };
+    let Ok(npc) = npc_query.get(entity) else {
+        return;
+    };
+    conversation.npc = Some(entity);
+
+    let opening = dialogues.get(&npc.template_id)
+        .and_then(|tree| tree.node(&tree.start));
+    if let Some(node) = opening {
+        conversation.node = Some(node.id.clone());
+        conversation.line = node.text.clone();
+    } else if let Some(template) = npc_registry.get(&npc.template_id) {
+        let hostile = template.faction.as_deref()
+            .zip(player_query.get_single().ok())
+            .is_some_and(|(faction, reputation)| reputation.with(faction) <= HOSTILE_REPUTATION);
+        conversation.line = if hostile {
+            COLD_GREETINGS.choose(&mut *rng).copied().unwrap_or_default().to_string()
+        } else {
+            generate_greeting(&template.dialogue_personality, &mut *rng)
+        };
+    }
+}
+
+/// Applies the chosen reply's effects and moves on to the node it leads to,
+/// ending the conversation if it doesn't lead anywhere
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn choose_response_system(
+    mut commands: Commands,
+    mut choose_events: EventReader<ChooseResponseEvent>,
+    mut conversation: ResMut<Conversation>,
+    mut dialog: ResMut<NpcDialog>,
+    npc_query: Query<&Npc, Without<Monster>>,
+    mut player_query: Query<(&Level, &mut Purse, &mut Inventory, &mut Health, Option<&mut Mana>, &Reputation, &mut DialogueFlags), With<Player>>,
+    mut item_query: Query<(&mut Item, &ItemTemplateRef)>,
+    dialogues: Res<DialogueRegistry>,
+    item_registry: Res<ItemTemplateRegistry>,
+    mut shop_events: EventWriter<OpenShopEvent>,
+    mut reputation_events: EventWriter<ReputationChangeEvent>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Some(event) = choose_events.read().last() else {
+        return;
+    };
+    let (Some(speaker), Some(node_id)) = (conversation.npc, conversation.node.clone()) else {
+        return;
+    };
+    let Some(tree) = npc_query.get(speaker).ok().and_then(|npc| dialogues.get(&npc.template_id)) else {
+        return;
+    };
+    let Some(response) = tree.node(&node_id).and_then(|node| node.responses.get(event.index)) else {
+        return;
+    };
+    let Ok((level, mut purse, mut inventory, mut health, mut mana, reputation, mut flags)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    let context = DialogueContext {
+        level: level.0,
+        gold: purse.gold,
+        carried: inventory.items.iter()
+            .filter_map(|&carried| item_query.get(carried).ok())
+            .map(|(_, template_ref)| template_ref.0.clone())
+            .collect(),
+        reputation,
+        flags: &flags,
+    };
+    if !context.allows_response(response) {
+        return;
+    }
+
+    for effect in &response.effects {
+        match effect {
+            DialogueEffect::GiveGold(gold) => {
+                purse.gold += gold;
+                message_log.add(format!("You receive {} gold.", gold), Color::GOLD);
+            }
+            DialogueEffect::TakeGold(gold) => {
+                purse.gold = purse.gold.saturating_sub(*gold);
+                message_log.add(format!("You hand over {} gold.", gold), Color::GOLD);
+            }
+            DialogueEffect::GiveItem(item_id) => {
+                let Some(template) = item_registry.get(item_id) else {
+                    warn!("Dialogue for '{}' gives unknown item '{}'", tree.npc, item_id);
+                    continue;
+                };
+                if inventory.is_full() {
+                    message_log.add(format!("You have no room for {}.", template.name()), Color::ORANGE);
+                    continue;
+                }
+                if let Some(given) = spawn_carried_item(&mut commands, &item_registry, item_id, 1) {
+                    inventory.items.push(given);
+                    message_log.add(format!("You receive {}.", template.name()), Color::LIME_GREEN);
+                }
+            }
+            DialogueEffect::TakeItem(item_id) => {
+                let Some(index) = inventory.items.iter()
+                    .position(|&carried| item_query.get(carried).is_ok_and(|(_, template_ref)| &template_ref.0 == item_id))
+                else {
+                    continue;
+                };
+                let carried = inventory.items[index];
+                if let Ok((mut item, _)) = item_query.get_mut(carried) {
+                    if item.stack_size > 1 {
+                        item.stack_size -= 1;
+                    } else {
+                        inventory.items.remove(index);
+                        commands.entity(carried).despawn_recursive();
+                    }
+                }
+                let name = item_registry.get(item_id).map_or(item_id.as_str(), |template| template.name());
+                message_log.add(format!("You hand over {}.", name), Color::GRAY);
+            }
+            DialogueEffect::SetFlag(flag) => {
+                flags.0.insert(flag.clone());
+            }
+            DialogueEffect::ClearFlag(flag) => {
+                flags.0.remove(flag);
+            }
+            DialogueEffect::ChangeReputation { faction, amount } => {
+                reputation_events.send(ReputationChangeEvent { faction: faction.clone(), amount: *amount });
+            }
+            DialogueEffect::Rest => {
+                health.current = health.max;
+                if let Some(mana) = mana.as_mut() {
+                    mana.current = mana.max;
+                }
+                message_log.add("You sleep soundly and wake refreshed.".to_string(), Color::LIME_GREEN);
+            }
+            DialogueEffect::OpenShop => {
+                shop_events.send(OpenShopEvent { merchant: speaker });
+            }
+        }
+    }
+
+    let Some(next) = response.next.as_deref() else {
+        dialog.npc = None;
+        return;
+    };
+    match tree.node(next) {
+        Some(next) => {
+            conversation.node = Some(next.id.clone());
+            conversation.line = next.text.clone();
+        }
+        None => {
+            warn!("Dialogue for '{}' leads to unknown node '{}'", tree.npc, next);
+            dialog.npc = None;
+        }
+    }
+}
+
+fn ask_rumor_system(
+    mut rumor_events: EventReader<AskRumorEvent>,
+    mut conversation: ResMut<Conversation>,
+    npc_query: Query<&Npc, Without<Monster>>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    mut rng: ResMut<GameRng>,
+) {
+    if rumor_events.read().last().is_none() {
+        return;
+    }
+    let Some(template) = conversation.npc
+        .and_then(|speaker| npc_query.get(speaker).ok())
+        .and_then(|npc| npc_registry.get(&npc.template_id))
+    else {
+        return;
+    };
+    conversation.line = generate_rumor(&template.dialogue_personality, &mut *rng);
+}
@@ -0,0 +1,136 @@
+// src/plugins/dungeon.rs
+
+use bevy::prelude::*;
+
+use crate::components::{Player, Position};
+use crate::game_state::GameState;
+use crate::plugins::map::{Map, MapBuilder, TileType, LEVEL_HEIGHT, LEVEL_WIDTH};
+use crate::plugins::player::TravelPlan;
+use crate::resources::{GameRng, GameWorld, MessageLog};
+use crate::systems::item_spawning::populate_items;
+use crate::systems::monster_spawning::{populate_level, MonsterDensityConfig};
+use crate::systems::npc_spawning::populate_npcs;
+use crate::templates::item_templates::ItemTemplateRegistry;
+use crate::templates::monster_templates::MonsterTemplateRegistry;
+use crate::templates::npc_templates::NPCTemplateRegistry;
+
+pub struct DungeonPlugin;
+
+impl Plugin for DungeonPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<UseStairsEvent>()
+            .add_systems(Update, use_stairs_system.run_if(in_state(GameState::Exploring)));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StairDirection {
+    Up,
+    Down,
+}
+
+/// Sent when the player tries to take the stairs they are standing on
+#[derive(Event)]
+pub struct UseStairsEvent {
+    pub direction: StairDirection,
+}
+
+/// Builds and populates the level at `depth`, storing it in the map. Returns the
+/// tile where a player coming from above arrives: the centre of the first room,
+/// which holds the up stairs on every level but the first.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_level(
+    commands: &mut Commands,
+    map: &mut Map,
+    registry: &MonsterTemplateRegistry,
+    item_registry: &ItemTemplateRegistry,
+    npc_registry: &NPCTemplateRegistry,
+    density: &MonsterDensityConfig,
+    depth: i32,
+    rng: &mut GameRng,
+) -> (i32, i32) {
+    let level_map = MapBuilder::new(LEVEL_WIDTH, LEVEL_HEIGHT)
+        .with_up_stairs(depth > 0)
+        .build(rng);
+
+    // Monsters and items never start in the arrival room
+    populate_level(commands, registry, density, &level_map, depth, 1, rng);
+    populate_items(commands, item_registry, &level_map, depth, 1, rng);
+    populate_npcs(commands, npc_registry, &level_map, depth, rng);
+
+    let arrival = level_map.rooms.first().map(|room| room.center()).unwrap_or((0, 0));
+    map.insert_level(depth, level_map);
+    info!("Generated dungeon level {}", depth);
+    arrival
+}
+
+/// Moves the player between levels. Levels are generated the first time they're
+/// entered and kept afterwards, along with their monsters, so going back up finds
+/// everything as it was left.
+#[allow(clippy::too_many_arguments)]
+fn use_stairs_system(
+    mut commands: Commands,
+    mut stairs_events: EventReader<UseStairsEvent>,
+    mut player_query: Query<&mut Position, With<Player>>,
+    mut map: ResMut<Map>,
+    registry: Res<MonsterTemplateRegistry>,
+    item_registry: Res<ItemTemplateRegistry>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    density: Res<MonsterDensityConfig>,
+    mut rng: ResMut<GameRng>,
+    mut game_world: ResMut<GameWorld>,
+    mut travel_plan: ResMut<TravelPlan>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Some(event) = stairs_events.read().last() else {
+        return;
+    };
+    let Ok(mut position) = player_query.get_single_mut() else {
+        return;
+    };
+
+    let tile = map.get_level(position.level)
+        .map_or(TileType::Wall, |level| level.get_tile(position.x, position.y));
+    let target = match (event.direction, tile) {
+        (StairDirection::Down, TileType::DownStairs) => position.level + 1,
+        (StairDirection::Up, TileType::UpStairs) => position.level - 1,
+        (StairDirection::Down, _) => {
+            message_log.add("There are no stairs leading down here.".to_string(), Color::GRAY);
+            return;
+        }
+        (StairDirection::Up, _) => {
+            message_log.add("There are no stairs leading up here.".to_string(), Color::GRAY);
+            return;
+        }
+    };
+
+    let generated = map.get_level(target).is_none()
+        .then(|| generate_level(&mut commands, &mut map, &registry, &item_registry, &npc_registry, &density, target, &mut rng));
+
+    // Arrive on the matching staircase of the other level
+    let arrival_tile = match event.direction {
+        StairDirection::Down => TileType::UpStairs,
+        StairDirection::Up => TileType::DownStairs,
+    };
+    let arrival = map.get_level(target)
+        .and_then(|level| level.find_tile(arrival_tile))
+        .or(generated)
+        .unwrap_or((position.x, position.y));
+
+    position.x = arrival.0;
+    position.y = arrival.1;
+    position.level = target;
+
+    // Taking the stairs uses a turn, but monsters on the new level don't get to
+    // act on it so the player is never ambushed on arrival
+    game_world.dungeon_level = target;
+    game_world.turn_count += 1;
+    *travel_plan = TravelPlan::Idle;
+
+    let verb = match event.direction {
+        StairDirection::Down => "descend",
+        StairDirection::Up => "climb",
+    };
+    message_log.add(format!("You {} to depth {}.", verb, target + 1), Color::WHITE);
+}
@@ -0,0 +1,284 @@
+// src/plugins/equipment.rs
+
+use bevy::prelude::*;
+
+use crate::components::{CombatStats, EquipSlot, Equipment, Inventory, Item, ItemType, Level, Monster, Name, Player, Stats, StatusEffect, StatusEffects};
+use crate::game_state::GameState;
+use crate::plugins::player::PlayerActedEvent;
+use crate::plugins::progression::stat_modifier;
+use crate::resources::{GameWorld, MessageLog};
+use crate::templates::item_templates::{ItemTemplate, ItemTemplateRef, ItemTemplateRegistry, ModifierType, StatModifier};
+use crate::templates::monster_templates::{MonsterTemplateRef, MonsterTemplateRegistry};
+
+/// Combat stats of an unequipped character with average (10) base stats
+const BASE_DAMAGE: i32 = 5;
+const BASE_DEFENSE: i32 = 2;
+const BASE_ACCURACY: i32 = 75;
+const BASE_EVASION: i32 = 10;
+
+pub struct EquipmentPlugin;
+
+impl Plugin for EquipmentPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<EquipItemEvent>()
+            .add_event::<UnequipItemEvent>()
+            .add_systems(
+                Update,
+                (equip_system, unequip_system)
+                    .run_if(in_state(GameState::Exploring))
+            )
+            // Runs in every state so stat changes from level ups in combat apply at once
+            .add_systems(PostUpdate, (recompute_combat_stats, recompute_monster_combat_stats));
+    }
+}
+
+/// Equips an item from the player's pack, swapping out whatever was in its slot
+#[derive(Event)]
+pub struct EquipItemEvent {
+    pub item: Entity,
+}
+
+/// Moves whatever is in a slot back into the player's pack
+#[derive(Event)]
+pub struct UnequipItemEvent {
+    pub slot: EquipSlot,
+}
+
+/// The slot an item goes in. Items spawned from templates know exactly; anything
+/// else falls back on its item type.
+pub fn slot_for(item: &Item, template: Option<&ItemTemplate>) -> Option<EquipSlot> {
+    match template {
+        Some(template) => template.equip_slot(),
+        None => match item.item_type {
+            ItemType::Weapon { .. } => Some(EquipSlot::Weapon),
+            ItemType::Armor { .. } => Some(EquipSlot::Armor),
+            _ => None,
+        },
+    }
+}
+
+/// Derives combat stats from base stats, everything equipped and status effects.
+/// Base stats set the starting values, gear adds its damage and defense, and then
+/// the gear's `StatModifier`s apply: flat bonuses first, percentages on top of the
+/// total. Buffs and curses count as flat modifiers. Modifiers can target base
+/// stats as well as combat stats.
+pub fn derive_combat_stats<'a>(
+    stats: &Stats,
+    gear: impl IntoIterator<Item = (&'a Item, Option<&'a ItemTemplate>)>,
+    status_effects: &[StatusEffect],
+) -> CombatStats {
+    let gear: Vec<_> = gear.into_iter().collect();
+    let status_modifiers = status_modifiers(status_effects);
+    let modifiers: Vec<&StatModifier> = gear.iter()
+        .filter_map(|(_, template)| *template)
+        .flat_map(|template| template.modifiers())
+        .chain(&status_modifiers)
+        .collect();
+    let apply = |stat: &str, value: i32| apply_modifiers(&modifiers, stat, value);
+
+    let strength = apply("strength", stats.strength);
+    let dexterity = apply("dexterity", stats.dexterity);
+
+    let mut damage = BASE_DAMAGE + stat_modifier(strength);
+    let mut defense = BASE_DEFENSE;
+    for (item, _) in gear.iter() {
+        match item.item_type {
+            ItemType::Weapon { damage: weapon_damage } => damage += weapon_damage,
+            ItemType::Armor { defense: armor_defense } => defense += armor_defense,
+            _ => {}
+        }
+    }
+
+    CombatStats {
+        damage: apply("damage", damage),
+        defense: apply("defense", defense),
+        accuracy: apply("accuracy", BASE_ACCURACY + stat_modifier(dexterity)),
+        evasion: apply("evasion", BASE_EVASION + stat_modifier(dexterity)),
+    }
+}
+
+/// A monster's combat stats with its status effects applied on top of what it
+/// was spawned with. Changes to strength and dexterity carry through to the
+/// combat stats they feed, the same way they do for the player.
+pub fn monster_combat_stats(base: &CombatStats, stats: &Stats, status_effects: &[StatusEffect]) -> CombatStats {
+    let status_modifiers = status_modifiers(status_effects);
+    let modifiers: Vec<&StatModifier> = status_modifiers.iter().collect();
+    let apply = |stat: &str, value: i32| apply_modifiers(&modifiers, stat, value);
+
+    let strength_change = stat_modifier(apply("strength", stats.strength)) - stat_modifier(stats.strength);
+    let dexterity_change = stat_modifier(apply("dexterity", stats.dexterity)) - stat_modifier(stats.dexterity);
+
+    CombatStats {
+        damage: apply("damage", base.damage + strength_change),
+        defense: apply("defense", base.defense),
+        accuracy: apply("accuracy", base.accuracy + dexterity_change),
+        evasion: apply("evasion", base.evasion + dexterity_change),
+    }
+}
+
+/// Buffs and curses as flat modifiers to the stats they change
+fn status_modifiers(status_effects: &[StatusEffect]) -> Vec<StatModifier> {
+    status_effects.iter()
+        .filter_map(|effect| effect.kind.stat_change())
+        .map(|(stat, amount)| StatModifier {
+            stat: stat.to_string(),
+            modifier_type: ModifierType::Flat,
+            value: amount as f32,
+        })
+        .collect()
+}
+
+fn apply_modifiers(modifiers: &[&StatModifier], stat: &str, value: i32) -> i32 {
+    let mut flat = 0.0;
+    let mut percent = 0.0;
+    for modifier in modifiers.iter().filter(|modifier| modifier.stat == stat) {
+        match modifier.modifier_type {
+            ModifierType::Flat => flat += modifier.value,
+            ModifierType::Percentage => percent += modifier.value,
+        }
+    }
+    ((value as f32 + flat) * (1.0 + percent / 100.0)).round() as i32
+}
+
+/// Describes the requirements the player falls short of, if any
+fn unmet_requirements(template: Option<&ItemTemplate>, stats: &Stats, level: i32) -> Vec<String> {
+    let Some(requirements) = template.and_then(|template| template.requirements()) else {
+        return Vec::new();
+    };
+
+    let mut unmet = Vec::new();
+    if let Some(required) = requirements.level.filter(|&required| level < required as i32) {
+        unmet.push(format!("level {}", required));
+    }
+    let stat_checks = [
+        ("strength", requirements.strength, stats.strength),
+        ("dexterity", requirements.dexterity, stats.dexterity),
+        ("intelligence", requirements.intelligence, stats.intelligence),
+    ];
+    for (stat, required, actual) in stat_checks {
+        if let Some(required) = required.filter(|&required| actual < required) {
+            unmet.push(format!("{} {}", required, stat));
+        }
+    }
+    unmet
+}
+
+fn equip_system(
+    mut equip_events: EventReader<EquipItemEvent>,
+    mut player_query: Query<(&mut Inventory, &mut Equipment, &Stats, &Level), With<Player>>,
+    item_query: Query<(&Item, &Name, Option<&ItemTemplateRef>)>,
+    registry: Res<ItemTemplateRegistry>,
+    mut game_world: ResMut<GameWorld>,
+    mut message_log: ResMut<MessageLog>,
+    mut acted_events: EventWriter<PlayerActedEvent>,
+) {
+    let Ok((mut inventory, mut equipment, stats, level)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for event in equip_events.read() {
+        let Some(index) = inventory.items.iter().position(|&item| item == event.item) else {
+            continue;
+        };
+        let Ok((item, name, template_ref)) = item_query.get(event.item) else {
+            continue;
+        };
+        let template = template_ref.and_then(|template_ref| registry.get(&template_ref.0));
+
+        let Some(slot) = slot_for(item, template) else {
+            message_log.add(format!("You can't equip {}.", name.0), Color::GRAY);
+            continue;
+        };
+
+        let unmet = unmet_requirements(template, stats, level.0);
+        if !unmet.is_empty() {
+            message_log.add(
+                format!("You need {} to use {}.", unmet.join(", "), name.0),
+                Color::ORANGE,
+            );
+            continue;
+        }
+
+        // The old item takes the new one's place in the pack, so a swap never needs a free slot
+        match equipment.slot_mut(slot).replace(event.item) {
+            Some(previous) => inventory.items[index] = previous,
+            None => {
+                inventory.items.remove(index);
+            }
+        }
+
+        message_log.add(format!("You equip {}.", name.0), Color::WHITE);
+        game_world.turn_count += 1;
+        acted_events.send(PlayerActedEvent);
+    }
+}
+
+fn unequip_system(
+    mut unequip_events: EventReader<UnequipItemEvent>,
+    mut player_query: Query<(&mut Inventory, &mut Equipment), With<Player>>,
+    name_query: Query<&Name, With<Item>>,
+    mut game_world: ResMut<GameWorld>,
+    mut message_log: ResMut<MessageLog>,
+    mut acted_events: EventWriter<PlayerActedEvent>,
+) {
+    let Ok((mut inventory, mut equipment)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for event in unequip_events.read() {
+        let Some(item) = equipment.slot(event.slot) else {
+            continue;
+        };
+        if inventory.is_full() {
+            message_log.add("Your pack is too full to take that off.".to_string(), Color::ORANGE);
+            continue;
+        }
+
+        *equipment.slot_mut(event.slot) = None;
+        inventory.items.push(item);
+
+        if let Ok(name) = name_query.get(item) {
+            message_log.add(format!("You take off {}.", name.0), Color::WHITE);
+        }
+        game_world.turn_count += 1;
+        acted_events.send(PlayerActedEvent);
+    }
+}
+
+/// Keeps the player's combat stats in step with their base stats, gear and status effects
+#[allow(clippy::type_complexity)]
+fn recompute_combat_stats(
+    mut player_query: Query<
+        (&Stats, &Equipment, Option<&StatusEffects>, &mut CombatStats),
+        (With<Player>, Or<(Changed<Stats>, Changed<Equipment>, Changed<StatusEffects>)>),
+    >,
+    item_query: Query<(&Item, Option<&ItemTemplateRef>)>,
+    registry: Res<ItemTemplateRegistry>,
+) {
+    for (stats, equipment, status_effects, mut combat_stats) in player_query.iter_mut() {
+        let gear = equipment.equipped()
+            .filter_map(|entity| item_query.get(entity).ok())
+            .map(|(item, template_ref)| (item, template_ref.and_then(|template_ref| registry.get(&template_ref.0))));
+        let status_effects = status_effects.map_or(&[][..], |effects| &effects.0[..]);
+        *combat_stats = derive_combat_stats(stats, gear, status_effects);
+    }
+}
+
+/// Keeps monsters' combat stats in step with the buffs and curses on them,
+/// starting from their template's stats at their level
+#[allow(clippy::type_complexity)]
+fn recompute_monster_combat_stats(
+    mut monster_query: Query<
+        (&Level, &Stats, &MonsterTemplateRef, &StatusEffects, &mut CombatStats),
+        (With<Monster>, Changed<StatusEffects>),
+    >,
+    templates: Res<MonsterTemplateRegistry>,
+) {
+    for (level, stats, template_ref, status_effects, mut combat_stats) in monster_query.iter_mut() {
+        let Some(template) = templates.get(&template_ref.0) else {
+            continue;
+        };
+        *combat_stats = monster_combat_stats(&template.combat_stats_at(level.0), stats, &status_effects.0);
+    }
+}
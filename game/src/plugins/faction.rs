@@ -0,0 +1,255 @@
+// src/plugins/faction.rs
+
+use bevy::prelude::*;
+
+use crate::components::{CombatStats, Health, Monster, Name, Npc, Player, Position, Reputation, Stats, Viewshed};
+use crate::game_state::GameState;
+use crate::plugins::combat::{MonsterSlainEvent, PlayerKilledMonsterEvent};
+use crate::plugins::combat_rules::{roll_attack, AttackOutcome, Combatant, CombatRuleset};
+use crate::plugins::map::MONSTER_VIEW_RANGE;
+use crate::plugins::npc::{calm_down, turn_hostile};
+use crate::resources::{GameRng, MessageLog};
+use crate::templates::faction_templates::FactionRegistry;
+use crate::templates::monster_templates::{MonsterTemplateRef, MonsterTemplateRegistry};
+use crate::templates::npc_templates::{NPCImportance, NPCTemplateRegistry, NPCType};
+
+/// Standing at or below which a faction counts the player as an enemy: its
+/// merchants won't deal with them and its guards attack on sight
+pub const HOSTILE_REPUTATION: i32 = -20;
+/// Reputation lost with a monster's faction for killing one of them
+const KILL_REPUTATION: i32 = -5;
+
+pub struct FactionPlugin;
+
+impl Plugin for FactionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<ReputationChangeEvent>()
+            .add_event::<FeudAttackEvent>()
+            .add_systems(
+                Update,
+                (reputation_from_kills, apply_reputation_changes)
+                    .chain()
+                    .run_if(in_state(GameState::Exploring).or_else(in_state(GameState::InCombat)))
+            )
+            // Monsters only brawl among themselves while the player is exploring
+            .add_systems(
+                Update,
+                (guard_hostility_system, calm_guards_system, resolve_feud_attacks).run_if(in_state(GameState::Exploring))
+            );
+    }
+}
+
+/// Changes the player's standing with a faction. Factions that feel strongly
+/// about it, one way or the other, change their opinion of the player as well.
+#[derive(Event)]
+pub struct ReputationChangeEvent {
+    pub faction: String,
+    pub amount: i32,
+}
+
+/// Sent when a monster attacks a monster from a faction its own is at war with
+#[derive(Event)]
+pub struct FeudAttackEvent {
+    pub attacker: Entity,
+    pub defender: Entity,
+}
+
+/// Marks a guard who took up arms because their faction counts the player as an
+/// enemy, rather than because the player attacked them
+#[derive(Component)]
+pub struct CalledToArms;
+
+/// The faction a monster, or an NPC turned hostile, belongs to
+pub fn faction_of<'a>(
+    template_ref: Option<&MonsterTemplateRef>,
+    npc: Option<&Npc>,
+    monster_templates: &'a MonsterTemplateRegistry,
+    npc_registry: &'a NPCTemplateRegistry,
+) -> Option<&'a str> {
+    let monster_faction = template_ref
+        .and_then(|template_ref| monster_templates.get(&template_ref.0))
+        .and_then(|template| template.faction.as_deref());
+    let npc_faction = || npc
+        .and_then(|npc| npc_registry.get(&npc.template_id))
+        .and_then(|template| template.faction.as_deref());
+    monster_faction.or_else(npc_faction)
+}
+
+/// How a faction regards the player, for showing alongside the number
+pub fn standing_label(reputation: i32) -> &'static str {
+    match reputation {
+        i32::MIN..=-50 => "Hated",
+        -49..=HOSTILE_REPUTATION => "Hostile",
+        -19..=-5 => "Disliked",
+        -4..=4 => "Neutral",
+        5..=19 => "Liked",
+        20..=49 => "Trusted",
+        _ => "Honored",
+    }
+}
+
+fn reputation_from_kills(
+    mut kill_events: EventReader<PlayerKilledMonsterEvent>,
+    mut reputation_events: EventWriter<ReputationChangeEvent>,
+) {
+    for event in kill_events.read() {
+        if let Some(faction) = &event.faction {
+            reputation_events.send(ReputationChangeEvent { faction: faction.clone(), amount: KILL_REPUTATION });
+        }
+    }
+}
+
+fn apply_reputation_changes(
+    mut reputation_events: EventReader<ReputationChangeEvent>,
+    mut player_query: Query<&mut Reputation, With<Player>>,
+    factions: Res<FactionRegistry>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok(mut reputation) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for event in reputation_events.read() {
+        if event.amount == 0 {
+            continue;
+        }
+        let change = if event.amount > 0 { "rises" } else { "falls" };
+        message_log.add(format!("Your standing with the {} {}.", factions.name(&event.faction), change), Color::GOLD);
+
+        // Friends of the faction take it the same way, its enemies the opposite
+        let mut changes = vec![(event.faction.as_str(), event.amount)];
+        for other in factions.all() {
+            let amount = event.amount * factions.relationship(&other.id, &event.faction) / 100;
+            if other.id != event.faction && amount != 0 {
+                changes.push((other.id.as_str(), amount));
+            }
+        }
+
+        for (faction, amount) in changes {
+            let before = reputation.with(faction);
+            let after = before + amount;
+            reputation.0.insert(faction.to_string(), after);
+            if before > HOSTILE_REPUTATION && after <= HOSTILE_REPUTATION {
+                message_log.add(format!("The {} now count you as an enemy.", factions.name(faction)), Color::ORANGE_RED);
+            } else if before <= HOSTILE_REPUTATION && after > HOSTILE_REPUTATION {
+                message_log.add(format!("The {} no longer count you as an enemy.", factions.name(faction)), Color::LIME_GREEN);
+            }
+        }
+    }
+}
+
+/// Whether an NPC is a guard who'll take up arms for their faction. Essential
+/// NPCs never do, as they can't be fought.
+fn faction_guard<'a>(npc_registry: &'a NPCTemplateRegistry, npc: &Npc) -> Option<&'a str> {
+    npc_registry.get(&npc.template_id)
+        .filter(|template| matches!(template.npc_type, NPCType::Guard) && template.importance != NPCImportance::Essential)
+        .and_then(|template| template.faction.as_deref())
+}
+
+/// Guards come after a player their faction counts as an enemy as soon as
+/// they're close enough to spot them
+fn guard_hostility_system(
+    mut commands: Commands,
+    player_query: Query<(&Position, &Reputation), With<Player>>,
+    npc_query: Query<(Entity, &Npc, &Name, &Position), Without<Monster>>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    factions: Res<FactionRegistry>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok((player_pos, reputation)) = player_query.get_single() else {
+        return;
+    };
+
+    for (entity, npc, name, position) in npc_query.iter() {
+        let Some(faction) = faction_guard(&npc_registry, npc) else {
+            continue;
+        };
+        let in_sight = position.level == player_pos.level
+            && (position.x - player_pos.x).abs() + (position.y - player_pos.y).abs() <= MONSTER_VIEW_RANGE;
+        if !in_sight || reputation.with(faction) > HOSTILE_REPUTATION {
+            continue;
+        }
+
+        message_log.add(
+            format!("{} shouts: \"You there! Halt, in the name of the {}!\"", name.0, factions.name(faction)),
+            Color::ORANGE_RED,
+        );
+        turn_hostile(&mut commands, entity, position);
+        commands.entity(entity).insert(CalledToArms);
+    }
+}
+
+/// Guards stand down once their faction no longer counts the player as an enemy.
+/// Those the player attacked themselves hold their grudge.
+#[allow(clippy::type_complexity)]
+fn calm_guards_system(
+    mut commands: Commands,
+    player_query: Query<&Reputation, With<Player>>,
+    guard_query: Query<(Entity, &Npc, &Name), (With<Monster>, With<CalledToArms>)>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok(reputation) = player_query.get_single() else {
+        return;
+    };
+
+    for (entity, npc, name) in guard_query.iter() {
+        let Some(faction) = faction_guard(&npc_registry, npc) else {
+            continue;
+        };
+        if reputation.with(faction) <= HOSTILE_REPUTATION {
+            continue;
+        }
+        message_log.add(format!("{} lowers their weapon.", name.0), Color::LIME_GREEN);
+        calm_down(&mut commands, entity);
+        commands.entity(entity).remove::<CalledToArms>();
+    }
+}
+
+/// One blow between monsters of warring factions. The player only hears about
+/// the fights they can see.
+pub fn resolve_feud_attacks(
+    mut feud_events: EventReader<FeudAttackEvent>,
+    mut monster_query: Query<(&Name, &Stats, &CombatStats, &mut Health, &Position), With<Monster>>,
+    player_query: Query<&Viewshed, With<Player>>,
+    rules: Res<CombatRuleset>,
+    mut rng: ResMut<GameRng>,
+    mut message_log: ResMut<MessageLog>,
+    mut slain_events: EventWriter<MonsterSlainEvent>,
+) {
+    for event in feud_events.read() {
+        let Ok([attacker, defender]) = monster_query.get_many_mut([event.attacker, event.defender]) else {
+            continue;
+        };
+        let (attacker_name, attacker_stats, attacker_combat, attacker_health, _) = attacker;
+        let (defender_name, defender_stats, defender_combat, mut defender_health, defender_pos) = defender;
+        // Either may have fallen earlier in the same turn
+        if attacker_health.current <= 0 || defender_health.current <= 0 {
+            continue;
+        }
+
+        let seen = player_query.get_single().is_ok_and(|viewshed| viewshed.can_see(defender_pos));
+        let attacking = Combatant { stats: attacker_stats, combat: attacker_combat, weapon: None, defending: false };
+        let defending = Combatant { stats: defender_stats, combat: defender_combat, weapon: None, defending: false };
+
+        match roll_attack(&**rules, &attacking, &defending, 1.0, &mut *rng) {
+            AttackOutcome::Hit { damage, .. } => {
+                defender_health.current -= damage;
+                if defender_health.current <= 0 {
+                    if seen {
+                        message_log.add(format!("{} cuts down {}!", attacker_name.0, defender_name.0), Color::ORANGE);
+                    }
+                    slain_events.send(MonsterSlainEvent { monster: event.defender, killer: Some(event.attacker) });
+                } else if seen {
+                    message_log.add(format!("{} hits {} for {} damage.", attacker_name.0, defender_name.0, damage), Color::GRAY);
+                }
+            }
+            AttackOutcome::Miss => {
+                if seen {
+                    message_log.add(format!("{} swings at {} and misses.", attacker_name.0, defender_name.0), Color::GRAY);
+                }
+            }
+        }
+    }
+}
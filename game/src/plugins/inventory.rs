@@ -0,0 +1,307 @@
+// src/plugins/inventory.rs
+
+use bevy::prelude::*;
+
+use crate::components::{Inventory, Item, ItemCharges, ItemCooldowns, ItemType, Name, Player, Position, Purse};
+use crate::game_state::GameState;
+use crate::plugins::combat::CurrentCombat;
+use crate::plugins::player::PlayerActedEvent;
+use crate::resources::{GameWorld, MessageLog};
+use crate::systems::consumable_effects::{turns, ApplyEffectsEvent};
+use crate::templates::item_templates::{ConsumableEffect, ItemTemplate, ItemTemplateRef, ItemTemplateRegistry};
+
+/// Number of stacks the player can carry
+pub const INVENTORY_CAPACITY: usize = 20;
+
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<PickupItemEvent>()
+            .add_event::<DropItemEvent>()
+            .add_event::<UseItemEvent>()
+            .init_resource::<InventoryWindow>()
+            .add_systems(
+                Update,
+                (
+                    inventory_input_system,
+                    pickup_system,
+                    drop_system,
+                )
+                .chain()
+                .run_if(in_state(GameState::Exploring))
+            )
+            // Items can be used on the player's turn in a fight as well
+            .add_systems(
+                Update,
+                (toggle_inventory_window, use_item_system)
+                    .run_if(in_state(GameState::Exploring).or_else(in_state(GameState::InCombat)))
+            );
+    }
+}
+
+/// Sent when the player tries to pick up whatever lies on their tile
+#[derive(Event)]
+pub struct PickupItemEvent;
+
+#[derive(Event)]
+pub struct DropItemEvent {
+    pub item: Entity,
+}
+
+#[derive(Event)]
+pub struct UseItemEvent {
+    pub item: Entity,
+}
+
+/// Whether the inventory window is showing
+#[derive(Resource, Default)]
+pub struct InventoryWindow {
+    pub open: bool,
+}
+
+fn inventory_input_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut pickup_events: EventWriter<PickupItemEvent>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyG) {
+        pickup_events.send(PickupItemEvent);
+    }
+}
+
+fn toggle_inventory_window(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut inventory_window: ResMut<InventoryWindow>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyI) {
+        inventory_window.open = !inventory_window.open;
+    }
+}
+
+/// Moves every item on the player's tile into their pack. Items merge into
+/// existing stacks of the same template first; whatever doesn't fit stays put.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn pickup_system(
+    mut commands: Commands,
+    mut pickup_events: EventReader<PickupItemEvent>,
+    mut player_query: Query<(&Position, &mut Inventory, &mut Purse), With<Player>>,
+    ground_query: Query<(Entity, &Position), (With<Item>, Without<Player>)>,
+    mut item_query: Query<(&mut Item, &Name, Option<&ItemTemplateRef>)>,
+    registry: Res<ItemTemplateRegistry>,
+    mut game_world: ResMut<GameWorld>,
+    mut message_log: ResMut<MessageLog>,
+    mut acted_events: EventWriter<PlayerActedEvent>,
+) {
+    if pickup_events.read().count() == 0 {
+        return;
+    }
+    let Ok((player_pos, mut inventory, mut purse)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    let here: Vec<Entity> = ground_query.iter()
+        .filter(|(_, pos)| **pos == *player_pos)
+        .map(|(entity, _)| entity)
+        .collect();
+    if here.is_empty() {
+        message_log.add("There is nothing here to pick up.".to_string(), Color::GRAY);
+        return;
+    }
+
+    let mut picked_up = false;
+    for entity in here {
+        let Ok((item, name, template_ref)) = item_query.get(entity) else {
+            continue;
+        };
+        let name = name.0.clone();
+
+        if let ItemType::Gold { amount } = item.item_type {
+            purse.gold += amount * item.stack_size;
+            commands.entity(entity).despawn_recursive();
+            message_log.add(format!("You pick up {} gold.", amount * item.stack_size), Color::GOLD);
+            picked_up = true;
+            continue;
+        }
+
+        let template_id = template_ref.map(|template_ref| template_ref.0.clone());
+        let max_stack = template_id.as_deref()
+            .and_then(|id| registry.get(id))
+            .map_or(1, |template| template.max_stack());
+        let total = item.stack_size;
+
+        // Top up stacks already in the pack
+        if let Some(template_id) = &template_id {
+            for &carried in inventory.items.iter() {
+                let Ok([(mut stack, _, carried_ref), (mut incoming, _, _)]) = item_query.get_many_mut([carried, entity]) else {
+                    continue;
+                };
+                if carried_ref.map(|r| &r.0) != Some(template_id) || stack.stack_size >= max_stack {
+                    continue;
+                }
+                let moved = (max_stack - stack.stack_size).min(incoming.stack_size);
+                stack.stack_size += moved;
+                incoming.stack_size -= moved;
+                if incoming.stack_size == 0 {
+                    break;
+                }
+            }
+        }
+
+        let Ok((remaining, _, _)) = item_query.get(entity) else {
+            continue;
+        };
+        let remaining = remaining.stack_size;
+        // Whatever didn't merge needs a free slot of its own
+        let stored = remaining > 0 && !inventory.is_full();
+        if remaining == 0 {
+            commands.entity(entity).despawn_recursive();
+        } else if stored {
+            commands.entity(entity).remove::<Position>();
+            inventory.items.push(entity);
+        }
+
+        let taken = if stored { total } else { total - remaining };
+        if taken > 0 {
+            picked_up = true;
+            if taken > 1 {
+                message_log.add(format!("You pick up {} x{}.", name, taken), Color::WHITE);
+            } else {
+                message_log.add(format!("You pick up {}.", name), Color::WHITE);
+            }
+        }
+        if taken < total {
+            message_log.add(format!("Your pack is full; you leave {} behind.", name), Color::ORANGE);
+        }
+    }
+
+    if picked_up {
+        game_world.turn_count += 1;
+        acted_events.send(PlayerActedEvent);
+    }
+}
+
+/// Puts a whole stack from the pack down on the player's tile
+fn drop_system(
+    mut commands: Commands,
+    mut drop_events: EventReader<DropItemEvent>,
+    mut player_query: Query<(&Position, &mut Inventory), With<Player>>,
+    name_query: Query<&Name, With<Item>>,
+    mut game_world: ResMut<GameWorld>,
+    mut message_log: ResMut<MessageLog>,
+    mut acted_events: EventWriter<PlayerActedEvent>,
+) {
+    let Ok((player_pos, mut inventory)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for event in drop_events.read() {
+        let Some(index) = inventory.items.iter().position(|&item| item == event.item) else {
+            continue;
+        };
+        inventory.items.remove(index);
+        commands.entity(event.item).insert(*player_pos);
+
+        if let Ok(name) = name_query.get(event.item) {
+            message_log.add(format!("You drop {}.", name.0), Color::WHITE);
+        }
+        game_world.turn_count += 1;
+        acted_events.send(PlayerActedEvent);
+    }
+}
+
+/// Uses one charge of an item in the pack. The item's effects are applied by the
+/// consumable effects executor; this pays for the use in charges, cooldown and time.
+#[allow(clippy::too_many_arguments)]
+fn use_item_system(
+    mut commands: Commands,
+    mut use_events: EventReader<UseItemEvent>,
+    mut player_query: Query<(Entity, &mut Inventory, Option<&mut ItemCooldowns>), With<Player>>,
+    mut item_query: Query<(&mut Item, &Name, Option<&ItemTemplateRef>, Option<&ItemCharges>)>,
+    registry: Res<ItemTemplateRegistry>,
+    state: Res<State<GameState>>,
+    mut current_combat: ResMut<CurrentCombat>,
+    mut game_world: ResMut<GameWorld>,
+    mut message_log: ResMut<MessageLog>,
+    mut effect_events: EventWriter<ApplyEffectsEvent>,
+    mut acted_events: EventWriter<PlayerActedEvent>,
+) {
+    let Ok((player, mut inventory, mut cooldowns)) = player_query.get_single_mut() else {
+        return;
+    };
+    let in_combat = *state.get() == GameState::InCombat;
+
+    for event in use_events.read() {
+        // In a fight, drinking a potion takes the player's turn like anything else
+        if in_combat && current_combat.current() != Some(player) {
+            continue;
+        }
+        if !inventory.items.contains(&event.item) {
+            continue;
+        }
+        let Ok((mut item, name, template_ref, charges)) = item_query.get_mut(event.item) else {
+            continue;
+        };
+        let template = template_ref
+            .and_then(|template_ref| registry.get(&template_ref.0))
+            .and_then(|template| match template {
+                ItemTemplate::Consumable(consumable) => Some(consumable),
+                _ => None,
+            });
+
+        let effects = match (template, &item.item_type) {
+            (Some(template), _) => template.effects.clone(),
+            (None, ItemType::Potion { heal_amount }) => vec![ConsumableEffect::Heal { amount: *heal_amount }],
+            _ => {
+                message_log.add(format!("You can't use {}.", name.0), Color::GRAY);
+                continue;
+            }
+        };
+
+        if let (Some(template), Some(cooldowns)) = (template, cooldowns.as_ref()) {
+            if let Some(&ready_at) = cooldowns.0.get(&template.id).filter(|&&ready_at| ready_at > game_world.turn_count) {
+                message_log.add(
+                    format!("{} won't be ready for another {} turns.", name.0, ready_at - game_world.turn_count),
+                    Color::GRAY,
+                );
+                continue;
+            }
+        }
+
+        message_log.add(format!("You use {}.", name.0), Color::WHITE);
+        effect_events.send(ApplyEffectsEvent {
+            target: player,
+            source: name.0.clone(),
+            effects,
+        });
+
+        // Multi-use items lose a charge; the last charge uses up one item of the stack
+        let max_charges = template.map_or(1, |template| template.charges.max(1));
+        let remaining = charges.map_or(max_charges, |charges| charges.0).saturating_sub(1);
+        if remaining > 0 {
+            commands.entity(event.item).insert(ItemCharges(remaining));
+            message_log.add(format!("{} has {} charges left.", name.0, remaining), Color::GRAY);
+        } else {
+            item.stack_size = item.stack_size.saturating_sub(1);
+            if item.stack_size == 0 {
+                inventory.items.retain(|&carried| carried != event.item);
+                commands.entity(event.item).despawn_recursive();
+            } else {
+                // The next item in the stack is untouched
+                commands.entity(event.item).remove::<ItemCharges>();
+            }
+        }
+
+        game_world.turn_count += 1;
+        if let (Some(template), Some(cooldowns)) = (template, cooldowns.as_mut()) {
+            if template.cooldown > 0.0 {
+                cooldowns.0.insert(template.id.clone(), game_world.turn_count + turns(template.cooldown));
+            }
+        }
+        if in_combat {
+            current_combat.end_turn();
+        } else {
+            acted_events.send(PlayerActedEvent);
+        }
+    }
+}
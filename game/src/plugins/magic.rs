@@ -0,0 +1,235 @@
+// src/plugins/magic.rs
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::components::{Health, Level, Mana, Monster, Name, Player, Position, Stats, StatusKind};
+use crate::game_state::GameState;
+use crate::plugins::combat::{damage_verb, resistance_message, CurrentCombat, MonsterSlainEvent};
+use crate::plugins::map::Map;
+use crate::plugins::player::PlayerActedEvent;
+use crate::plugins::progression::stat_modifier;
+use crate::resources::{GameRng, GameWorld, MessageLog};
+use crate::systems::status_effects::ApplyStatusEvent;
+use crate::templates::monster_templates::{MonsterTemplateRef, MonsterTemplateRegistry};
+use crate::templates::spell_templates::{SpellEffect, SpellTarget, SpellTemplateRegistry};
+
+/// Maximum mana for every point of intelligence
+const MANA_PER_INTELLIGENCE: i32 = 2;
+/// Mana comes back one point every this many turns
+const MANA_REGEN_TURNS: u32 = 5;
+
+pub struct MagicPlugin;
+
+impl Plugin for MagicPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<CastSpellEvent>()
+            .init_resource::<SpellbookWindow>()
+            .add_systems(
+                Update,
+                (spellbook_input_system, cast_spell_system, regenerate_mana)
+                    .chain()
+                    .run_if(in_state(GameState::Exploring).or_else(in_state(GameState::InCombat)))
+            )
+            // Alongside the combat stats, so a level up's intelligence shows at once
+            .add_systems(PostUpdate, sync_max_mana);
+    }
+}
+
+/// Sent when the player casts one of their spells
+#[derive(Event)]
+pub struct CastSpellEvent {
+    pub spell_id: String,
+}
+
+/// Whether the spellbook window is showing
+#[derive(Resource, Default)]
+pub struct SpellbookWindow {
+    pub open: bool,
+}
+
+pub fn max_mana(stats: &Stats) -> i32 {
+    (stats.intelligence * MANA_PER_INTELLIGENCE).max(0)
+}
+
+fn spellbook_input_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut spellbook_window: ResMut<SpellbookWindow>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyC) {
+        spellbook_window.open = !spellbook_window.open;
+    }
+}
+
+/// Casts a spell the player knows. Spells aimed at an enemy hit the current
+/// combat target; everything else lands on the player. Casting takes a turn.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn cast_spell_system(
+    mut cast_events: EventReader<CastSpellEvent>,
+    mut player_query: Query<(Entity, &Stats, &Level, &Position, &mut Mana, &mut Health), With<Player>>,
+    mut monster_query: Query<(&Name, &mut Health, Option<&MonsterTemplateRef>), (With<Monster>, Without<Player>)>,
+    spells: Res<SpellTemplateRegistry>,
+    monster_templates: Res<MonsterTemplateRegistry>,
+    state: Res<State<GameState>>,
+    mut current_combat: ResMut<CurrentCombat>,
+    mut map: ResMut<Map>,
+    mut game_world: ResMut<GameWorld>,
+    mut rng: ResMut<GameRng>,
+    mut message_log: ResMut<MessageLog>,
+    mut status_events: EventWriter<ApplyStatusEvent>,
+    mut slain_events: EventWriter<MonsterSlainEvent>,
+    mut acted_events: EventWriter<PlayerActedEvent>,
+) {
+    let Ok((player, stats, level, position, mut mana, mut player_health)) = player_query.get_single_mut() else {
+        return;
+    };
+    let in_combat = *state.get() == GameState::InCombat;
+
+    for event in cast_events.read() {
+        if in_combat && current_combat.current() != Some(player) {
+            continue;
+        }
+        let Some(spell) = spells.get(&event.spell_id) else {
+            warn!("Tried to cast unknown spell '{}'", event.spell_id);
+            continue;
+        };
+        if spell.level > level.0 {
+            message_log.add(format!("You haven't learned {} yet.", spell.name), Color::GRAY);
+            continue;
+        }
+        if mana.current < spell.mana_cost {
+            message_log.add(format!("You don't have enough mana to cast {}.", spell.name), Color::GRAY);
+            continue;
+        }
+        let target = match spell.target {
+            SpellTarget::Caster => player,
+            SpellTarget::Enemy => match current_combat.target.filter(|_| in_combat) {
+                Some(target) => target,
+                None => {
+                    message_log.add(format!("There's nothing to cast {} at.", spell.name), Color::GRAY);
+                    continue;
+                }
+            },
+        };
+
+        mana.current -= spell.mana_cost;
+        message_log.add(format!("You cast {}!", spell.name), Color::VIOLET);
+
+        for effect in &spell.effects {
+            match effect {
+                SpellEffect::Damage { damage } => {
+                    let Ok((name, mut health, template_ref)) = monster_query.get_mut(target) else {
+                        continue;
+                    };
+                    let multiplier = template_ref
+                        .and_then(|template_ref| monster_templates.get(&template_ref.0))
+                        .map_or(1.0, |template| template.damage_multiplier(damage.damage_type));
+                    let roll = rng.gen_range(damage.min..=damage.max.max(damage.min)) + stat_modifier(stats.intelligence);
+                    let dealt = (roll.max(1) as f32 * multiplier).round() as i32;
+                    health.current -= dealt;
+
+                    message_log.add(
+                        format!(
+                            "{} {} {} for {} {} damage!",
+                            spell.name, damage_verb(damage.damage_type), name.0, dealt, damage.damage_type.name(),
+                        ),
+                        Color::RED,
+                    );
+                    if let Some((message, color)) = resistance_message(&name.0, damage.damage_type, multiplier) {
+                        message_log.add(message, color);
+                    }
+
+                    if health.current <= 0 {
+                        message_log.add(format!("{} has been slain!", name.0), Color::DARK_GRAY);
+                        current_combat.remove(target);
+                        slain_events.send(MonsterSlainEvent { monster: target, killer: Some(player) });
+                        // Nothing left for the rest of the spell to land on
+                        break;
+                    }
+                }
+                SpellEffect::Heal { amount } => {
+                    let mut health = if target == player {
+                        player_health.reborrow()
+                    } else {
+                        match monster_query.get_mut(target) {
+                            Ok((_, health, _)) => health,
+                            Err(_) => continue,
+                        }
+                    };
+                    let healed = (*amount).min(health.max - health.current).max(0);
+                    health.current += healed;
+                    message_log.add(format!("{} heals {} HP.", spell.name, healed), Color::LIME_GREEN);
+                }
+                SpellEffect::Status { status, duration } => {
+                    status_events.send(ApplyStatusEvent {
+                        target,
+                        source: spell.name.clone(),
+                        kind: status.clone(),
+                        duration: *duration,
+                    });
+                }
+                SpellEffect::Light { radius } => {
+                    let Some(level_map) = map.get_level_mut(position.level) else {
+                        continue;
+                    };
+                    for y in position.y - radius..=position.y + radius {
+                        for x in position.x - radius..=position.x + radius {
+                            if level_map.in_bounds(x, y) && (x - position.x).pow(2) + (y - position.y).pow(2) <= radius * radius {
+                                level_map.reveal(x, y);
+                            }
+                        }
+                    }
+                    message_log.add("Light floods the area around you.".to_string(), Color::YELLOW);
+                }
+                SpellEffect::DetectMonsters { radius, duration } => {
+                    status_events.send(ApplyStatusEvent {
+                        target,
+                        source: spell.name.clone(),
+                        kind: StatusKind::DetectMonsters { radius: *radius },
+                        duration: *duration,
+                    });
+                }
+            }
+        }
+
+        game_world.turn_count += 1;
+        if in_combat {
+            current_combat.end_turn();
+        } else {
+            acted_events.send(PlayerActedEvent);
+        }
+    }
+}
+
+/// Gives back a point of mana for every few turns that have passed
+fn regenerate_mana(
+    mut casters: Query<&mut Mana>,
+    game_world: Res<GameWorld>,
+) {
+    let turn = game_world.turn_count;
+    for mut mana in casters.iter_mut() {
+        if mana.last_regen >= turn {
+            continue;
+        }
+        let regained = (turn / MANA_REGEN_TURNS - mana.last_regen / MANA_REGEN_TURNS) as i32;
+        mana.current = (mana.current + regained).min(mana.max);
+        mana.last_regen = turn;
+    }
+}
+
+/// Keeps maximum mana in step with intelligence. A higher maximum comes with
+/// the extra mana to fill it.
+fn sync_max_mana(mut casters: Query<(&Stats, &mut Mana), Changed<Stats>>) {
+    for (stats, mut mana) in casters.iter_mut() {
+        let max = max_mana(stats);
+        if max == mana.max {
+            continue;
+        }
+        if max > mana.max {
+            mana.current += max - mana.max;
+        }
+        mana.max = max;
+        mana.current = mana.current.min(max);
+    }
+}
@@ -0,0 +1,155 @@
+// src/plugins/map/fov.rs
+use std::collections::HashSet;
+
+use super::LevelMap;
+
+/// Computes the set of tiles visible from `origin` using symmetric shadowcasting
+/// (https://www.albertford.com/shadowcasting/). Symmetry means that if A can see B
+/// then B can see A, so the player and monsters share the same visibility rules.
+pub fn compute_fov(level: &LevelMap, origin: (i32, i32), range: i32) -> HashSet<(i32, i32)> {
+    let mut visible = HashSet::new();
+    visible.insert(origin);
+
+    for quadrant in [Quadrant::North, Quadrant::East, Quadrant::South, Quadrant::West] {
+        let mut caster = Caster {
+            level,
+            origin,
+            range,
+            quadrant,
+            visible: &mut visible,
+        };
+        caster.scan(Row {
+            depth: 1,
+            start_slope: Slope::new(-1, 1),
+            end_slope: Slope::new(1, 1),
+        });
+    }
+
+    visible
+}
+
+#[derive(Clone, Copy)]
+enum Quadrant {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Quadrant {
+    /// Converts a (row depth, column) pair relative to the quadrant into map coordinates
+    fn transform(&self, origin: (i32, i32), depth: i32, col: i32) -> (i32, i32) {
+        let (ox, oy) = origin;
+        match self {
+            Quadrant::North => (ox + col, oy + depth),
+            Quadrant::South => (ox + col, oy - depth),
+            Quadrant::East => (ox + depth, oy + col),
+            Quadrant::West => (ox - depth, oy + col),
+        }
+    }
+}
+
+/// An exact rational slope, kept as integers so the symmetry checks never suffer
+/// from floating point rounding. The denominator is always positive.
+#[derive(Clone, Copy)]
+struct Slope {
+    num: i32,
+    den: i32,
+}
+
+impl Slope {
+    fn new(num: i32, den: i32) -> Self {
+        Self { num, den }
+    }
+
+    /// Slope through the leading edge of the tile at (depth, col)
+    fn of_tile(depth: i32, col: i32) -> Self {
+        Self::new(2 * col - 1, 2 * depth)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Row {
+    depth: i32,
+    start_slope: Slope,
+    end_slope: Slope,
+}
+
+impl Row {
+    fn min_col(&self) -> i32 {
+        // round_ties_up(depth * start_slope) = floor(depth * num / den + 1/2)
+        (2 * self.depth * self.start_slope.num + self.start_slope.den)
+            .div_euclid(2 * self.start_slope.den)
+    }
+
+    fn max_col(&self) -> i32 {
+        // round_ties_down(depth * end_slope) = ceil(depth * num / den - 1/2)
+        -(self.end_slope.den - 2 * self.depth * self.end_slope.num)
+            .div_euclid(2 * self.end_slope.den)
+    }
+
+    /// Floor tiles are only revealed when they lie within the row's slopes,
+    /// which is what makes the algorithm symmetric
+    fn is_symmetric(&self, col: i32) -> bool {
+        col * self.start_slope.den >= self.depth * self.start_slope.num
+            && col * self.end_slope.den <= self.depth * self.end_slope.num
+    }
+
+    fn next(&self) -> Row {
+        Row {
+            depth: self.depth + 1,
+            ..*self
+        }
+    }
+}
+
+struct Caster<'a> {
+    level: &'a LevelMap,
+    origin: (i32, i32),
+    range: i32,
+    quadrant: Quadrant,
+    visible: &'a mut HashSet<(i32, i32)>,
+}
+
+impl Caster<'_> {
+    fn is_opaque(&self, depth: i32, col: i32) -> bool {
+        let (x, y) = self.quadrant.transform(self.origin, depth, col);
+        self.level.get_tile(x, y).blocks_sight()
+    }
+
+    fn reveal(&mut self, depth: i32, col: i32) {
+        // Trim the square scan down to a circle
+        if depth * depth + col * col <= self.range * self.range + self.range {
+            let tile = self.quadrant.transform(self.origin, depth, col);
+            self.visible.insert(tile);
+        }
+    }
+
+    fn scan(&mut self, mut row: Row) {
+        if row.depth > self.range {
+            return;
+        }
+
+        let mut prev_opaque: Option<bool> = None;
+        for col in row.min_col()..=row.max_col() {
+            let opaque = self.is_opaque(row.depth, col);
+
+            if opaque || row.is_symmetric(col) {
+                self.reveal(row.depth, col);
+            }
+            if prev_opaque == Some(true) && !opaque {
+                row.start_slope = Slope::of_tile(row.depth, col);
+            }
+            if prev_opaque == Some(false) && opaque {
+                let mut next_row = row.next();
+                next_row.end_slope = Slope::of_tile(row.depth, col);
+                self.scan(next_row);
+            }
+            prev_opaque = Some(opaque);
+        }
+
+        if prev_opaque == Some(false) {
+            self.scan(row.next());
+        }
+    }
+}
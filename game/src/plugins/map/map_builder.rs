@@ -0,0 +1,144 @@
+// src/plugins/map/map_builder.rs
+use rand::prelude::*;
+
+use super::{LevelMap, Rect, TileType};
+
+/// Generates a single dungeon level using the classic rooms-and-corridors approach:
+/// rectangular rooms are scattered without overlapping and then joined in order by
+/// L-shaped corridors. Doors are placed where a corridor breaks through a room wall.
+pub struct MapBuilder {
+    width: i32,
+    height: i32,
+    max_rooms: usize,
+    room_min_size: i32,
+    room_max_size: i32,
+    up_stairs: bool,
+}
+
+impl MapBuilder {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            max_rooms: 30,
+            room_min_size: 4,
+            room_max_size: 10,
+            up_stairs: false,
+        }
+    }
+
+    /// Places up stairs in the first room, for every level below the first
+    pub fn with_up_stairs(mut self, up_stairs: bool) -> Self {
+        self.up_stairs = up_stairs;
+        self
+    }
+
+    pub fn build<R: Rng + ?Sized>(&self, rng: &mut R) -> LevelMap {
+        let mut map = LevelMap::new(self.width, self.height);
+
+        for _ in 0..self.max_rooms {
+            let w = rng.gen_range(self.room_min_size..=self.room_max_size);
+            let h = rng.gen_range(self.room_min_size..=self.room_max_size);
+            // Keep a one tile wall border around the whole level
+            if w + 2 >= self.width || h + 2 >= self.height {
+                continue;
+            }
+            let x = rng.gen_range(1..self.width - w - 1);
+            let y = rng.gen_range(1..self.height - h - 1);
+            let room = Rect::new(x, y, w, h);
+
+            // Rooms must not touch, so their walls stay intact for door placement
+            if map.rooms.iter().any(|other| room.intersects(&other.expanded(1))) {
+                continue;
+            }
+
+            carve_room(&mut map, &room);
+
+            if let Some(previous) = map.rooms.last() {
+                let (new_x, new_y) = room.center();
+                let (prev_x, prev_y) = previous.center();
+                if rng.gen_bool(0.5) {
+                    carve_horizontal_tunnel(&mut map, prev_x, new_x, prev_y);
+                    carve_vertical_tunnel(&mut map, prev_y, new_y, new_x);
+                } else {
+                    carve_vertical_tunnel(&mut map, prev_y, new_y, prev_x);
+                    carve_horizontal_tunnel(&mut map, prev_x, new_x, new_y);
+                }
+            }
+
+            map.rooms.push(room);
+        }
+
+        place_doors(&mut map);
+
+        let up_stairs = map.rooms.first()
+            .filter(|_| self.up_stairs)
+            .map(|first| first.center());
+        if let Some(last) = map.rooms.last() {
+            let (mut x, y) = last.center();
+            // With a single room both staircases share it. Rooms are at least four
+            // tiles wide, so the tile right of the centre is always floor.
+            if up_stairs == Some((x, y)) {
+                x += 1;
+            }
+            map.set_tile(x, y, TileType::DownStairs);
+        }
+        if let Some((x, y)) = up_stairs {
+            map.set_tile(x, y, TileType::UpStairs);
+        }
+
+        map
+    }
+}
+
+fn carve_room(map: &mut LevelMap, room: &Rect) {
+    for y in room.y1..=room.y2 {
+        for x in room.x1..=room.x2 {
+            map.set_tile(x, y, TileType::Floor);
+        }
+    }
+}
+
+fn carve_horizontal_tunnel(map: &mut LevelMap, x1: i32, x2: i32, y: i32) {
+    for x in x1.min(x2)..=x1.max(x2) {
+        map.set_tile(x, y, TileType::Floor);
+    }
+}
+
+fn carve_vertical_tunnel(map: &mut LevelMap, y1: i32, y2: i32, x: i32) {
+    for y in y1.min(y2)..=y1.max(y2) {
+        map.set_tile(x, y, TileType::Floor);
+    }
+}
+
+/// Turns corridor openings in room walls into doors. An opening only counts as a
+/// doorway when the wall continues on both sides of it, so corridors that run
+/// along a wall don't produce a row of doors.
+fn place_doors(map: &mut LevelMap) {
+    let rooms = map.rooms.clone();
+    for room in &rooms {
+        let ring = room.expanded(1);
+
+        for x in room.x1..=room.x2 {
+            for y in [ring.y1, ring.y2] {
+                if map.get_tile(x, y) == TileType::Floor
+                    && map.get_tile(x - 1, y) == TileType::Wall
+                    && map.get_tile(x + 1, y) == TileType::Wall
+                {
+                    map.set_tile(x, y, TileType::Door);
+                }
+            }
+        }
+
+        for y in room.y1..=room.y2 {
+            for x in [ring.x1, ring.x2] {
+                if map.get_tile(x, y) == TileType::Floor
+                    && map.get_tile(x, y - 1) == TileType::Wall
+                    && map.get_tile(x, y + 1) == TileType::Wall
+                {
+                    map.set_tile(x, y, TileType::Door);
+                }
+            }
+        }
+    }
+}
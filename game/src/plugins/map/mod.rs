@@ -1 +1,261 @@
 // src/plugins/map/mod.rs
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::components::{Player, Position, Viewshed};
+
+pub mod fov;
+pub mod map_builder;
+pub mod pathfinding;
+
+pub use fov::compute_fov;
+pub use map_builder::MapBuilder;
+pub use pathfinding::PathfindingCache;
+
+/// Size of every generated dungeon level, in tiles
+pub const LEVEL_WIDTH: i32 = 80;
+pub const LEVEL_HEIGHT: i32 = 50;
+
+/// Sight radius, in tiles
+pub const PLAYER_VIEW_RANGE: i32 = 8;
+pub const MONSTER_VIEW_RANGE: i32 = 6;
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Map>()
+            .init_resource::<PathfindingCache>()
+            .add_systems(Update, visibility_system);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TileType {
+    Floor,
+    Wall,
+    Door,
+    DownStairs,
+    UpStairs,
+}
+
+impl TileType {
+    pub fn glyph(&self) -> char {
+        match self {
+            TileType::Floor => '.',
+            TileType::Wall => '#',
+            TileType::Door => '+',
+            TileType::DownStairs => '>',
+            TileType::UpStairs => '<',
+        }
+    }
+
+    pub fn is_walkable(&self) -> bool {
+        !matches!(self, TileType::Wall)
+    }
+
+    /// Walls and doors can't be seen through
+    pub fn blocks_sight(&self) -> bool {
+        matches!(self, TileType::Wall | TileType::Door)
+    }
+}
+
+/// Axis-aligned rectangle with inclusive bounds, used for room interiors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rect {
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            x1: x,
+            y1: y,
+            x2: x + width - 1,
+            y2: y + height - 1,
+        }
+    }
+
+    pub fn center(&self) -> (i32, i32) {
+        ((self.x1 + self.x2) / 2, (self.y1 + self.y2) / 2)
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x1 <= other.x2 && self.x2 >= other.x1 && self.y1 <= other.y2 && self.y2 >= other.y1
+    }
+
+    /// Returns a copy grown by `amount` tiles on every side
+    pub fn expanded(&self, amount: i32) -> Rect {
+        Rect {
+            x1: self.x1 - amount,
+            y1: self.y1 - amount,
+            x2: self.x2 + amount,
+            y2: self.y2 + amount,
+        }
+    }
+}
+
+static NEXT_REVISION: AtomicU64 = AtomicU64::new(1);
+
+/// Hands out revision numbers that are unique for the whole process, so a level
+/// that is regenerated or loaded from disk never matches a stale cache entry
+fn next_revision() -> u64 {
+    NEXT_REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// The tile grid for a single dungeon level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelMap {
+    pub width: i32,
+    pub height: i32,
+    pub tiles: Vec<TileType>,
+    /// Tiles the player has seen at least once, drawn from memory when out of view
+    pub revealed: Vec<bool>,
+    pub rooms: Vec<Rect>,
+    /// Changes whenever a tile changes, so derived data like path graphs can tell they're stale
+    #[serde(skip, default = "next_revision")]
+    revision: u64,
+}
+
+impl LevelMap {
+    /// Creates a level that is solid wall everywhere
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![TileType::Wall; (width * height) as usize],
+            revealed: vec![false; (width * height) as usize],
+            rooms: Vec::new(),
+            revision: next_revision(),
+        }
+    }
+
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && x < self.width && y >= 0 && y < self.height
+    }
+
+    fn idx(&self, x: i32, y: i32) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    /// Anything outside the level is treated as solid wall
+    pub fn get_tile(&self, x: i32, y: i32) -> TileType {
+        if self.in_bounds(x, y) {
+            self.tiles[self.idx(x, y)]
+        } else {
+            TileType::Wall
+        }
+    }
+
+    pub fn set_tile(&mut self, x: i32, y: i32, tile: TileType) {
+        if self.in_bounds(x, y) {
+            let idx = self.idx(x, y);
+            if self.tiles[idx] != tile {
+                self.tiles[idx] = tile;
+                self.revision = next_revision();
+            }
+        }
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn is_walkable(&self, x: i32, y: i32) -> bool {
+        self.get_tile(x, y).is_walkable()
+    }
+
+    pub fn is_revealed(&self, x: i32, y: i32) -> bool {
+        self.in_bounds(x, y) && self.revealed[self.idx(x, y)]
+    }
+
+    pub fn reveal(&mut self, x: i32, y: i32) {
+        if self.in_bounds(x, y) {
+            let idx = self.idx(x, y);
+            self.revealed[idx] = true;
+        }
+    }
+
+    /// The first tile of the given type, scanning row by row
+    pub fn find_tile(&self, tile: TileType) -> Option<(i32, i32)> {
+        let idx = self.tiles.iter().position(|&t| t == tile)? as i32;
+        Some((idx % self.width, idx / self.width))
+    }
+
+    /// All walkable tiles inside the given room
+    pub fn floor_tiles_in(&self, room: &Rect) -> Vec<(i32, i32)> {
+        let mut tiles = Vec::new();
+        for y in room.y1..=room.y2 {
+            for x in room.x1..=room.x2 {
+                if self.get_tile(x, y) == TileType::Floor {
+                    tiles.push((x, y));
+                }
+            }
+        }
+        tiles
+    }
+}
+
+/// Resource holding the generated tile grid of every dungeon level, keyed by `Position.level`
+#[derive(Resource, Default)]
+pub struct Map {
+    pub levels: HashMap<i32, LevelMap>,
+}
+
+impl Map {
+    pub fn get_level(&self, level: i32) -> Option<&LevelMap> {
+        self.levels.get(&level)
+    }
+
+    pub fn get_level_mut(&mut self, level: i32) -> Option<&mut LevelMap> {
+        self.levels.get_mut(&level)
+    }
+
+    pub fn insert_level(&mut self, level: i32, level_map: LevelMap) {
+        self.levels.insert(level, level_map);
+    }
+
+    /// Whether an entity could stand at the given position. Levels that haven't
+    /// been generated are never walkable.
+    pub fn is_walkable(&self, position: &Position) -> bool {
+        self.get_level(position.level)
+            .is_some_and(|level| level.is_walkable(position.x, position.y))
+    }
+
+    pub fn clear(&mut self) {
+        self.levels.clear();
+    }
+}
+
+/// Recomputes the field of view of every entity that moved or was flagged dirty.
+/// Whatever the player sees is also remembered on the level map.
+fn visibility_system(
+    mut map: ResMut<Map>,
+    mut viewers: Query<(&mut Viewshed, Ref<Position>, Option<&Player>)>,
+) {
+    for (mut viewshed, position, player) in viewers.iter_mut() {
+        if !viewshed.dirty && !position.is_changed() {
+            continue;
+        }
+
+        let Some(level) = map.get_level(position.level) else {
+            continue;
+        };
+        viewshed.visible_tiles = compute_fov(level, (position.x, position.y), viewshed.range);
+        viewshed.dirty = false;
+
+        if player.is_some() {
+            if let Some(level) = map.get_level_mut(position.level) {
+                for &(x, y) in viewshed.visible_tiles.iter() {
+                    level.reveal(x, y);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+// src/plugins/map/pathfinding.rs
+use bevy::prelude::*;
+use petgraph::algo::astar;
+use petgraph::graph::{NodeIndex, UnGraph};
+use petgraph::visit::{EdgeFiltered, EdgeRef};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use super::{LevelMap, Map};
+
+/// Cost of a single step in a Dijkstra map. Costs are kept as integers scaled by
+/// ten so flee maps can apply their 1.2 multiplier without floating point.
+pub const STEP_COST: i32 = 10;
+/// Extra cost for stepping onto a tile another creature stands on. Paths route
+/// around crowds where they can but are never completely cut off by them.
+const OCCUPIED_COST: i32 = 10;
+/// Multiplier applied to a distance field to turn it into a flee map. Values above
+/// 1.0 make fleeing creatures prefer long escape routes over cornering themselves.
+const FLEE_FACTOR_TENTHS: i32 = 12;
+
+const DIRECTIONS: [(i32, i32); 4] = [(0, 1), (0, -1), (-1, 0), (1, 0)];
+
+/// Per-level graphs built from the tile grid. A graph is rebuilt lazily the next
+/// time it is requested after its level's tiles have changed.
+#[derive(Resource, Default)]
+pub struct PathfindingCache {
+    graphs: HashMap<i32, LevelGraph>,
+}
+
+impl PathfindingCache {
+    /// The graph for a level, rebuilding it if the level's tiles changed since it was cached
+    pub fn graph(&mut self, map: &Map, level: i32) -> Option<&LevelGraph> {
+        let level_map = map.get_level(level)?;
+        let stale = self.graphs
+            .get(&level)
+            .is_none_or(|graph| graph.revision != level_map.revision());
+        if stale {
+            self.graphs.insert(level, LevelGraph::build(level_map));
+        }
+        self.graphs.get(&level)
+    }
+}
+
+/// The walkable tiles of one level, joined to their four neighbours
+pub struct LevelGraph {
+    revision: u64,
+    graph: UnGraph<(i32, i32), ()>,
+    nodes: HashMap<(i32, i32), NodeIndex>,
+}
+
+impl LevelGraph {
+    pub fn build(level_map: &LevelMap) -> Self {
+        let mut graph = UnGraph::new_undirected();
+        let mut nodes = HashMap::new();
+
+        for y in 0..level_map.height {
+            for x in 0..level_map.width {
+                if level_map.is_walkable(x, y) {
+                    nodes.insert((x, y), graph.add_node((x, y)));
+                }
+            }
+        }
+
+        // Only link right and up so each edge is added once. Walked in grid order
+        // rather than over the map so the edge order, and with it how A* breaks
+        // ties, is the same on every run.
+        for y in 0..level_map.height {
+            for x in 0..level_map.width {
+                let Some(&node) = nodes.get(&(x, y)) else {
+                    continue;
+                };
+                for neighbour in [(x + 1, y), (x, y + 1)] {
+                    if let Some(&other) = nodes.get(&neighbour) {
+                        graph.add_edge(node, other, ());
+                    }
+                }
+            }
+        }
+
+        Self {
+            revision: level_map.revision(),
+            graph,
+            nodes,
+        }
+    }
+
+    /// Shortest path from `start` to `goal` using A*, excluding `start` itself.
+    /// Only tiles accepted by `passable` are considered (the goal always is), and
+    /// tiles in `occupied` are avoided when there's a reasonable way around.
+    pub fn find_path(
+        &self,
+        start: (i32, i32),
+        goal: (i32, i32),
+        occupied: &HashSet<(i32, i32)>,
+        passable: impl Fn((i32, i32)) -> bool,
+    ) -> Option<Vec<(i32, i32)>> {
+        let &start_node = self.nodes.get(&start)?;
+        let &goal_node = self.nodes.get(&goal)?;
+
+        let filtered = EdgeFiltered::from_fn(&self.graph, |edge| {
+            let tile = self.graph[edge.target()];
+            tile == goal || passable(tile)
+        });
+
+        let (_, path) = astar(
+            &filtered,
+            start_node,
+            |node| node == goal_node,
+            |edge| {
+                let tile = self.graph[edge.target()];
+                if tile != goal && occupied.contains(&tile) {
+                    1 + OCCUPIED_COST / STEP_COST
+                } else {
+                    1
+                }
+            },
+            |node| {
+                let (x, y) = self.graph[node];
+                (x - goal.0).abs() + (y - goal.1).abs()
+            },
+        )?;
+
+        Some(path.into_iter().skip(1).map(|node| self.graph[node]).collect())
+    }
+
+    /// Distance field from every reachable tile to the nearest of `goals`
+    pub fn dijkstra_map(&self, goals: &[(i32, i32)], passable: impl Fn((i32, i32)) -> bool) -> DijkstraMap {
+        let seeds = goals.iter().map(|&goal| (goal, 0));
+        self.scan(seeds, passable)
+    }
+
+    /// A map that leads away from `threats`. Built the classic way: the distance
+    /// field is inverted and scaled, then rescanned so that following it downhill
+    /// heads for the safest reachable spot rather than the nearest dead end.
+    pub fn flee_map(&self, threats: &[(i32, i32)], passable: impl Fn((i32, i32)) -> bool + Copy) -> DijkstraMap {
+        let distances = self.dijkstra_map(threats, passable);
+        let seeds = distances.values
+            .iter()
+            .map(|(&tile, &value)| (tile, -value * FLEE_FACTOR_TENTHS / 10));
+        self.scan(seeds, passable)
+    }
+
+    /// Multi-source Dijkstra over the level graph, starting from the given seed values
+    fn scan(
+        &self,
+        seeds: impl Iterator<Item = ((i32, i32), i32)>,
+        passable: impl Fn((i32, i32)) -> bool,
+    ) -> DijkstraMap {
+        let mut values: HashMap<(i32, i32), i32> = HashMap::new();
+        let mut frontier = BinaryHeap::new();
+
+        for (tile, value) in seeds {
+            if let Some(&node) = self.nodes.get(&tile) {
+                let best = values.entry(tile).or_insert(value);
+                *best = (*best).min(value);
+                frontier.push(Reverse((*best, node)));
+            }
+        }
+
+        while let Some(Reverse((value, node))) = frontier.pop() {
+            let tile = self.graph[node];
+            if values.get(&tile).is_some_and(|&best| value > best) {
+                continue;
+            }
+            for neighbour in self.graph.neighbors(node) {
+                let next_tile = self.graph[neighbour];
+                if !passable(next_tile) {
+                    continue;
+                }
+                let next_value = value + STEP_COST;
+                if values.get(&next_tile).is_none_or(|&best| next_value < best) {
+                    values.insert(next_tile, next_value);
+                    frontier.push(Reverse((next_value, neighbour)));
+                }
+            }
+        }
+
+        DijkstraMap { values }
+    }
+}
+
+/// A value for every reachable tile; creatures move by stepping downhill
+pub struct DijkstraMap {
+    values: HashMap<(i32, i32), i32>,
+}
+
+impl DijkstraMap {
+    pub fn value(&self, tile: (i32, i32)) -> Option<i32> {
+        self.values.get(&tile).copied()
+    }
+
+    /// The neighbouring tile with the lowest value, if it is lower than `from`.
+    /// Tiles in `blocked` are never chosen.
+    pub fn next_step(&self, from: (i32, i32), blocked: &HashSet<(i32, i32)>) -> Option<(i32, i32)> {
+        let current = self.value(from).unwrap_or(i32::MAX);
+        DIRECTIONS.iter()
+            .map(|(dx, dy)| (from.0 + dx, from.1 + dy))
+            .filter(|tile| !blocked.contains(tile))
+            .filter_map(|tile| self.value(tile).map(|value| (tile, value)))
+            .filter(|&(_, value)| value < current)
+            .min_by_key(|&(_, value)| value)
+            .map(|(tile, _)| tile)
+    }
+}
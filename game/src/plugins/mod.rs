@@ -2,13 +2,38 @@
 use bevy::prelude::*;
 
 pub mod combat;
+pub mod combat_rules;
+pub mod dialogue;
+pub mod dungeon;
+pub mod equipment;
+pub mod faction;
+pub mod inventory;
+pub mod magic;
+pub mod map;
 pub mod monster;
+pub mod npc;
 pub mod player;
+pub mod progression;
+pub mod quest;
+pub mod save;
+pub mod shop;
 pub mod ui;
 
 pub use combat::CombatPlugin;
+pub use dialogue::DialoguePlugin;
+pub use dungeon::DungeonPlugin;
+pub use equipment::EquipmentPlugin;
+pub use faction::FactionPlugin;
+pub use inventory::InventoryPlugin;
+pub use magic::MagicPlugin;
+pub use map::MapPlugin;
 pub use monster::MonsterPlugin;
+pub use npc::NpcPlugin;
 pub use player::PlayerPlugin;
+pub use progression::ProgressionPlugin;
+pub use quest::QuestPlugin;
+pub use save::SavePlugin;
+pub use shop::ShopPlugin;
 pub use ui::UiPlugin;
 
 // This plugin will bundle all our game-specific plugins.
@@ -17,10 +42,25 @@ pub struct GamePlugin;
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
+            MapPlugin,
+            DungeonPlugin,
             UiPlugin,
             PlayerPlugin,
+            InventoryPlugin,
+            EquipmentPlugin,
+            MagicPlugin,
+            ProgressionPlugin,
             CombatPlugin,
             MonsterPlugin,
+            // Plugin tuples top out at fifteen, so the townsfolk get one of their own
+            (
+                NpcPlugin,
+                ShopPlugin,
+                DialoguePlugin,
+                QuestPlugin,
+                FactionPlugin,
+            ),
+            SavePlugin,
         ));
     }
 }
\ No newline at end of file
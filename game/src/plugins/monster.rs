@@ -1,56 +1,241 @@
-// src/plugins/monster.rs - DEBUG VERSION
+// src/plugins/monster.rs
 
 use bevy::prelude::*;
-use crate::components::{Monster, Player};
-use crate::plugins::combat::AttackEvent;
-use crate::resources::TurnState;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::components::{AIType, Health, Monster, Name, Npc, Player, Position, Viewshed};
+use crate::game_state::GameState;
+use crate::plugins::combat::StartCombatEvent;
+use crate::plugins::faction::{faction_of, FeudAttackEvent};
+use crate::plugins::map::{Map, PathfindingCache};
+use crate::plugins::player::PlayerActedEvent;
+use crate::resources::{GameRng, MessageLog};
+use crate::templates::faction_templates::FactionRegistry;
+use crate::templates::monster_templates::{MonsterTemplateRef, MonsterTemplateRegistry};
+use crate::templates::npc_templates::NPCTemplateRegistry;
+
+/// How far a defensive monster will stray from its home tile to engage the player
+const GUARD_RADIUS: i32 = 4;
+/// Defensive monsters retreat once their health drops below this fraction
+const RETREAT_HEALTH_RATIO: f32 = 0.3;
+/// Chance that a passive monster shuffles to a neighbouring tile on its turn
+const WANDER_CHANCE: f64 = 0.5;
+
+const DIRECTIONS: [(i32, i32); 4] = [(0, 1), (0, -1), (-1, 0), (1, 0)];
 
 pub struct MonsterPlugin;
 
 impl Plugin for MonsterPlugin {
-    fn build(&self, _app: &mut App) {
-        // The monster AI system is registered in the CombatPlugin
+    fn build(&self, app: &mut App) {
+        // In-combat monster turns are driven by the CombatPlugin; these run while exploring
+        app.add_systems(
+            Update,
+            (
+                assign_monster_homes,
+                monster_turn_system,
+            )
+            .chain()
+            .run_if(in_state(GameState::Exploring))
+        );
     }
 }
 
-pub fn monster_ai_system(
-    turn_state: Option<Res<TurnState>>,
-    mut attack_events: EventWriter<AttackEvent>,
-    player_query: Query<Entity, With<Player>>,
-    monster_query: Query<Entity, With<Monster>>,
+/// The tile a defensive monster guards and returns to
+#[derive(Component, Clone, Copy, Serialize, Deserialize)]
+pub struct MonsterHome {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Monsters remember where they were first seen as their home
+#[allow(clippy::type_complexity)]
+fn assign_monster_homes(
+    mut commands: Commands,
+    monsters: Query<(Entity, &Position), (With<Monster>, Without<MonsterHome>)>,
 ) {
-    info!("Monster AI system running");
-    
-    // Check if we have turn state
-    let Some(turn) = turn_state else {
-        error!("No TurnState in monster_ai_system!");
-        return;
-    };
-    
-    info!("Monster AI checking turn: {:?}", *turn);
-    
-    // Only act during monster turn
-    if *turn != TurnState::MonsterTurn {
-        info!("Not monster turn, skipping");
+    for (entity, position) in monsters.iter() {
+        commands.entity(entity).insert(MonsterHome { x: position.x, y: position.y });
+    }
+}
+
+/// Gives every monster on the player's level one action for each action the player took.
+/// Monsters that aren't busy with the player go after any rival from a faction theirs is at war with.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn monster_turn_system(
+    mut acted_events: EventReader<PlayerActedEvent>,
+    player_query: Query<&Position, (With<Player>, Without<Monster>)>,
+    mut monster_query: Query<(Entity, &Monster, &mut Position, &Health, &Viewshed, &Name, Option<&MonsterHome>, Option<&MonsterTemplateRef>, Option<&Npc>), Without<Player>>,
+    npc_query: Query<&Position, (With<Npc>, Without<Monster>)>,
+    map: Res<Map>,
+    monster_templates: Res<MonsterTemplateRegistry>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    factions: Res<FactionRegistry>,
+    mut pathfinding: ResMut<PathfindingCache>,
+    mut rng: ResMut<GameRng>,
+    mut message_log: ResMut<MessageLog>,
+    mut combat_events: EventWriter<StartCombatEvent>,
+    mut feud_events: EventWriter<FeudAttackEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let turns = acted_events.read().count();
+    if turns == 0 {
         return;
     }
-    
-    info!("It's monster turn!");
-    
-    // Get entities
-    match (player_query.get_single(), monster_query.get_single()) {
-        (Ok(player_entity), Ok(monster_entity)) => {
-            info!("Monster {:?} attacking Player {:?}", monster_entity, player_entity);
-            attack_events.send(AttackEvent {
-                attacker: monster_entity,
-                defender: player_entity,
+
+    let Ok(player_pos) = player_query.get_single() else {
+        return;
+    };
+    let player_pos = *player_pos;
+
+    for _ in 0..turns {
+        let mut occupied: HashSet<(i32, i32)> = monster_query.iter()
+            .filter(|(_, _, pos, ..)| pos.level == player_pos.level)
+            .map(|(_, _, pos, ..)| (pos.x, pos.y))
+            // Monsters walk around townsfolk rather than through them
+            .chain(npc_query.iter().filter(|pos| pos.level == player_pos.level).map(|pos| (pos.x, pos.y)))
+            .collect();
+        // Where everyone with a faction stands, kept up to date as they move
+        let mut factioned: HashMap<Entity, (&str, (i32, i32))> = monster_query.iter()
+            .filter(|(_, _, pos, ..)| pos.level == player_pos.level)
+            .filter_map(|(entity, _, pos, _, _, _, _, template_ref, npc)| {
+                faction_of(template_ref, npc, &monster_templates, &npc_registry)
+                    .map(|faction| (entity, (faction, (pos.x, pos.y))))
+            })
+            .collect();
+
+        for (entity, monster, mut position, health, viewshed, name, home, ..) in monster_query.iter_mut() {
+            if position.level != player_pos.level {
+                continue;
+            }
+
+            let sees_player = viewshed.can_see(&player_pos);
+            let here = (position.x, position.y);
+            let target = (player_pos.x, player_pos.y);
+            // The closest enemy of the monster's faction it can see
+            let rival = factioned.get(&entity).and_then(|&(faction, _)| {
+                factioned.iter()
+                    .filter(|&(_, &(other, (x, y)))| {
+                        factions.is_hostile(faction, other) && viewshed.can_see(&Position { x, y, level: position.level })
+                    })
+                    .min_by_key(|&(&other, &(_, tile))| (distance(here, tile), other))
+                    .map(|(&other, &(_, tile))| (other, tile))
             });
+
+            let action = match monster.ai_type {
+                AIType::Aggressive => {
+                    if sees_player {
+                        chase(here, target)
+                    } else if let Some((rival, tile)) = rival {
+                        brawl(here, rival, tile)
+                    } else {
+                        MonsterAction::Wait
+                    }
+                }
+                AIType::Defensive => {
+                    let home = home.map_or(here, |home| (home.x, home.y));
+                    let health_ratio = health.current as f32 / health.max as f32;
+                    if sees_player && health_ratio < RETREAT_HEALTH_RATIO {
+                        MonsterAction::StepAwayFrom(target)
+                    } else if sees_player && distance(home, target) <= GUARD_RADIUS {
+                        chase(here, target)
+                    } else if let Some((rival, tile)) = rival.filter(|&(_, tile)| distance(home, tile) <= GUARD_RADIUS) {
+                        brawl(here, rival, tile)
+                    } else if here != home {
+                        MonsterAction::StepTowards(home)
+                    } else {
+                        MonsterAction::Wait
+                    }
+                }
+                AIType::Passive => {
+                    if rng.gen_bool(WANDER_CHANCE) {
+                        MonsterAction::Wander
+                    } else {
+                        MonsterAction::Wait
+                    }
+                }
+            };
+
+            let destination = match action {
+                MonsterAction::Attack => {
+                    message_log.add(format!("{} attacks you!", name.0), Color::ORANGE_RED);
+                    combat_events.send(StartCombatEvent { monster: entity });
+                    next_state.set(GameState::InCombat);
+                    // Any other hostiles close by are drawn into the same fight when it starts
+                    return;
+                }
+                MonsterAction::Brawl(rival) => {
+                    feud_events.send(FeudAttackEvent { attacker: entity, defender: rival });
+                    None
+                }
+                MonsterAction::StepTowards(goal) => pathfinding.graph(&map, position.level)
+                    .and_then(|graph| graph.find_path(here, goal, &occupied, |_| true))
+                    .and_then(|path| path.first().copied())
+                    // Blocked by another monster this turn; wait rather than shove past
+                    .filter(|&next| can_enter(next, &map, position.level, &occupied, target)),
+                MonsterAction::StepAwayFrom(threat) => pathfinding.graph(&map, position.level)
+                    .and_then(|graph| {
+                        let mut blocked = occupied.clone();
+                        blocked.insert(target);
+                        graph.flee_map(&[threat], |_| true).next_step(here, &blocked)
+                    }),
+                MonsterAction::Wander => {
+                    let &(dx, dy) = DIRECTIONS.choose(&mut *rng).unwrap();
+                    Some((here.0 + dx, here.1 + dy))
+                        .filter(|&next| can_enter(next, &map, position.level, &occupied, target))
+                }
+                MonsterAction::Wait => None,
+            };
+
+            if let Some((x, y)) = destination {
+                occupied.remove(&here);
+                occupied.insert((x, y));
+                position.x = x;
+                position.y = y;
+                if let Some((_, tile)) = factioned.get_mut(&entity) {
+                    *tile = (x, y);
+                }
+            }
         }
-        (Err(e1), _) => {
-            error!("Failed to get player for monster AI: {:?}", e1);
-        }
-        (_, Err(e2)) => {
-            error!("Failed to get monster for monster AI: {:?}", e2);
-        }
     }
-}
\ No newline at end of file
+}
+
+enum MonsterAction {
+    Attack,
+    /// Attack a monster of a rival faction
+    Brawl(Entity),
+    StepTowards((i32, i32)),
+    StepAwayFrom((i32, i32)),
+    Wander,
+    Wait,
+}
+
+/// Attack when adjacent, otherwise close the distance
+fn chase(here: (i32, i32), target: (i32, i32)) -> MonsterAction {
+    if distance(here, target) == 1 {
+        MonsterAction::Attack
+    } else {
+        MonsterAction::StepTowards(target)
+    }
+}
+
+/// Attack a rival when adjacent, otherwise close the distance
+fn brawl(here: (i32, i32), rival: Entity, tile: (i32, i32)) -> MonsterAction {
+    if distance(here, tile) == 1 {
+        MonsterAction::Brawl(rival)
+    } else {
+        MonsterAction::StepTowards(tile)
+    }
+}
+
+/// Manhattan distance, matching the four-way movement used everywhere
+fn distance(a: (i32, i32), b: (i32, i32)) -> i32 {
+    (a.0 - b.0).abs() + (a.1 - b.1).abs()
+}
+
+fn can_enter(tile: (i32, i32), map: &Map, level: i32, occupied: &HashSet<(i32, i32)>, player: (i32, i32)) -> bool {
+    tile != player
+        && !occupied.contains(&tile)
+        && map.is_walkable(&Position { x: tile.0, y: tile.1, level })
+}
@@ -0,0 +1,164 @@
+// src/plugins/npc.rs
+
+use bevy::prelude::*;
+
+use crate::components::{AIType, Monster, Name, Npc, Player, Position, Viewshed};
+use crate::game_state::GameState;
+use crate::plugins::combat::StartCombatEvent;
+use crate::plugins::faction::ReputationChangeEvent;
+use crate::plugins::map::MONSTER_VIEW_RANGE;
+use crate::plugins::monster::MonsterHome;
+use crate::plugins::player::TravelPlan;
+use crate::resources::MessageLog;
+use crate::templates::npc_templates::{NPCImportance, NPCTemplateRegistry};
+
+/// Standing lost with an NPC's faction for attacking them
+const ASSAULT_REPUTATION: i32 = -30;
+
+pub struct NpcPlugin;
+
+impl Plugin for NpcPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<TalkToNpcEvent>()
+            .add_event::<AttackNpcEvent>()
+            .init_resource::<NpcDialog>()
+            .add_systems(
+                Update,
+                (talk_to_npc_system, attack_npc_system, close_dialog_when_apart)
+                    .chain()
+                    .run_if(in_state(GameState::Exploring))
+            )
+            .add_systems(OnExit(GameState::Exploring), close_dialog);
+    }
+}
+
+/// Sent when the player bumps into a peaceful NPC
+#[derive(Event)]
+pub struct TalkToNpcEvent {
+    pub npc: Entity,
+}
+
+/// Sent when the player chooses to attack the NPC they're talking to
+#[derive(Event)]
+pub struct AttackNpcEvent {
+    pub npc: Entity,
+}
+
+/// The NPC the player is talking to, if any
+#[derive(Resource, Default)]
+pub struct NpcDialog {
+    pub npc: Option<Entity>,
+    /// The player has been told attacking this NPC has consequences
+    pub attack_warned: bool,
+}
+
+fn talk_to_npc_system(
+    mut talk_events: EventReader<TalkToNpcEvent>,
+    npc_query: Query<&Name, (With<Npc>, Without<Monster>)>,
+    mut dialog: ResMut<NpcDialog>,
+    mut travel_plan: ResMut<TravelPlan>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Some(event) = talk_events.read().last() else {
+        return;
+    };
+    let Ok(name) = npc_query.get(event.npc) else {
+        return;
+    };
+
+    // Walking into someone ends the trip rather than bumping them every step
+    *travel_plan = TravelPlan::Idle;
+    if dialog.npc != Some(event.npc) {
+        message_log.add(format!("You talk to {}.", name.0), Color::WHITE);
+        *dialog = NpcDialog { npc: Some(event.npc), attack_warned: false };
+    }
+}
+
+/// Turns an NPC hostile, unless it's one the game can't do without. Attacking
+/// an important NPC has to be asked for twice.
+#[allow(clippy::too_many_arguments)]
+fn attack_npc_system(
+    mut commands: Commands,
+    mut attack_events: EventReader<AttackNpcEvent>,
+    npc_query: Query<(&Npc, &Name, &Position), Without<Monster>>,
+    registry: Res<NPCTemplateRegistry>,
+    mut dialog: ResMut<NpcDialog>,
+    mut message_log: ResMut<MessageLog>,
+    mut combat_events: EventWriter<StartCombatEvent>,
+    mut reputation_events: EventWriter<ReputationChangeEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(event) = attack_events.read().last() else {
+        return;
+    };
+    let Ok((npc, name, position)) = npc_query.get(event.npc) else {
+        return;
+    };
+    let importance = registry.get(&npc.template_id)
+        .map_or(NPCImportance::Normal, |template| template.importance);
+
+    match importance {
+        NPCImportance::Essential => {
+            message_log.add(format!("You can't bring yourself to raise a hand against {}.", name.0), Color::GRAY);
+            return;
+        }
+        NPCImportance::Important if !(dialog.attack_warned && dialog.npc == Some(event.npc)) => {
+            message_log.add(
+                format!("{} is well known around here. Attacking them won't go unnoticed.", name.0),
+                Color::ORANGE,
+            );
+            dialog.attack_warned = true;
+            return;
+        }
+        _ => {}
+    }
+
+    message_log.add(format!("{} turns on you!", name.0), Color::ORANGE_RED);
+    turn_hostile(&mut commands, event.npc, position);
+    // Word of an unprovoked attack gets back to the NPC's own
+    if let Some(faction) = registry.get(&npc.template_id).and_then(|template| template.faction.clone()) {
+        reputation_events.send(ReputationChangeEvent { faction, amount: ASSAULT_REPUTATION });
+    }
+    combat_events.send(StartCombatEvent { monster: event.npc });
+    next_state.set(GameState::InCombat);
+}
+
+/// Makes an NPC a monster that comes for the player, guarding where it stood
+pub fn turn_hostile(commands: &mut Commands, npc: Entity, position: &Position) {
+    commands.entity(npc).insert((
+        Monster { ai_type: AIType::Aggressive },
+        Viewshed::new(MONSTER_VIEW_RANGE),
+        MonsterHome { x: position.x, y: position.y },
+    ));
+}
+
+/// Undoes `turn_hostile`, making the NPC someone to talk to again
+pub fn calm_down(commands: &mut Commands, npc: Entity) {
+    commands.entity(npc).remove::<(Monster, Viewshed, MonsterHome)>();
+}
+
+/// The conversation is over once the player walks off
+fn close_dialog_when_apart(
+    mut dialog: ResMut<NpcDialog>,
+    player_query: Query<&Position, With<Player>>,
+    npc_query: Query<&Position, (With<Npc>, Without<Monster>)>,
+) {
+    let Some(npc) = dialog.npc else {
+        return;
+    };
+    let together = match (player_query.get_single(), npc_query.get(npc)) {
+        (Ok(player_pos), Ok(npc_pos)) => {
+            player_pos.level == npc_pos.level
+                && (player_pos.x - npc_pos.x).abs() + (player_pos.y - npc_pos.y).abs() <= 1
+        }
+        _ => false,
+    };
+    if !together {
+        *dialog = NpcDialog::default();
+    }
+}
+
+fn close_dialog(mut dialog: ResMut<NpcDialog>) {
+    *dialog = NpcDialog::default();
+}
@@ -1,10 +1,18 @@
 // src/plugins/player.rs - FIXED FOR MULTIPLE MONSTERS
 
 use bevy::prelude::*;
-use crate::components::{Monster, Player, Position, Name};
+use std::collections::{HashSet, VecDeque};
+
+use crate::components::{Monster, Npc, Player, Position, Name, Viewshed};
 use crate::game_state::GameState;
-use crate::resources::MessageLog;
+use crate::plugins::map::{LevelMap, Map, PathfindingCache};
+use crate::resources::{GameWorld, MessageLog};
 use super::combat::StartCombatEvent;
+use super::dungeon::{StairDirection, UseStairsEvent};
+use super::npc::TalkToNpcEvent;
+
+/// Seconds between steps while auto-exploring or travelling, so the player can follow along
+const TRAVEL_STEP_SECONDS: f32 = 0.08;
 
 pub struct PlayerPlugin;
 
@@ -12,15 +20,22 @@ impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_event::<PlayerMoveEvent>()
+            .add_event::<PlayerActedEvent>()
+            .add_event::<TravelToEvent>()
+            .init_resource::<TravelPlan>()
             .add_systems(
                 Update,
                 (
                     player_input_system,
+                    plan_travel_system,
+                    travel_system,
                     movement_system,
                 )
                 .chain()
                 .run_if(in_state(GameState::Exploring))
-            );
+            )
+            // Any interruption (combat, menus) ends the current trip
+            .add_systems(OnExit(GameState::Exploring), cancel_travel);
     }
 }
 
@@ -30,36 +45,218 @@ pub struct PlayerMoveEvent {
     pub dy: i32,
 }
 
+/// Sent whenever the player spends a turn outside of combat, so the rest of the
+/// world (monster AI, timers) can take its turn too
+#[derive(Event)]
+pub struct PlayerActedEvent;
+
+/// Asks the player to walk to a tile, e.g. from clicking on the map
+#[derive(Event)]
+pub struct TravelToEvent {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Multi-turn movement the player has committed to
+#[derive(Resource, Default)]
+pub enum TravelPlan {
+    #[default]
+    Idle,
+    /// Head for the nearest unexplored area until there is none left
+    Exploring,
+    /// Follow a precomputed path, one tile per step
+    Travelling(VecDeque<(i32, i32)>),
+}
+
 fn player_input_system(
     keyboard: Res<ButtonInput<KeyCode>>,
     mut player_move_events: EventWriter<PlayerMoveEvent>,
+    mut stairs_events: EventWriter<UseStairsEvent>,
+    mut travel_plan: ResMut<TravelPlan>,
+    mut message_log: ResMut<MessageLog>,
 ) {
+    let mut moved = false;
     if keyboard.just_pressed(KeyCode::KeyW) || keyboard.just_pressed(KeyCode::ArrowUp) {
         player_move_events.send(PlayerMoveEvent { dx: 0, dy: 1 });
+        moved = true;
     }
     if keyboard.just_pressed(KeyCode::KeyS) || keyboard.just_pressed(KeyCode::ArrowDown) {
         player_move_events.send(PlayerMoveEvent { dx: 0, dy: -1 });
+        moved = true;
     }
     if keyboard.just_pressed(KeyCode::KeyA) || keyboard.just_pressed(KeyCode::ArrowLeft) {
         player_move_events.send(PlayerMoveEvent { dx: -1, dy: 0 });
+        moved = true;
     }
     if keyboard.just_pressed(KeyCode::KeyD) || keyboard.just_pressed(KeyCode::ArrowRight) {
         player_move_events.send(PlayerMoveEvent { dx: 1, dy: 0 });
+        moved = true;
+    }
+    // '>' and '<' share their keys with '.' and ','
+    if keyboard.just_pressed(KeyCode::Period) {
+        stairs_events.send(UseStairsEvent { direction: StairDirection::Down });
+        moved = true;
+    }
+    if keyboard.just_pressed(KeyCode::Comma) {
+        stairs_events.send(UseStairsEvent { direction: StairDirection::Up });
+        moved = true;
+    }
+
+    // Manual movement always takes over from auto-travel
+    if moved {
+        *travel_plan = TravelPlan::Idle;
+    } else if keyboard.just_pressed(KeyCode::KeyX) {
+        *travel_plan = match *travel_plan {
+            TravelPlan::Exploring => TravelPlan::Idle,
+            _ => {
+                message_log.add("You start exploring.".to_string(), Color::GRAY);
+                TravelPlan::Exploring
+            }
+        };
+    }
+}
+
+/// Turns travel requests into a path over tiles the player already knows about
+fn plan_travel_system(
+    mut travel_events: EventReader<TravelToEvent>,
+    player_query: Query<&Position, With<Player>>,
+    map: Res<Map>,
+    mut pathfinding: ResMut<PathfindingCache>,
+    mut travel_plan: ResMut<TravelPlan>,
+) {
+    let Some(event) = travel_events.read().last() else {
+        return;
+    };
+    let Ok(player_pos) = player_query.get_single() else {
+        return;
+    };
+    let Some(level_map) = map.get_level(player_pos.level) else {
+        return;
+    };
+    if !level_map.is_revealed(event.x, event.y) {
+        return;
+    }
+
+    let path = pathfinding.graph(&map, player_pos.level).and_then(|graph| {
+        graph.find_path(
+            (player_pos.x, player_pos.y),
+            (event.x, event.y),
+            &HashSet::new(),
+            |(x, y)| level_map.is_revealed(x, y),
+        )
+    });
+    if let Some(path) = path {
+        *travel_plan = TravelPlan::Travelling(path.into());
     }
 }
 
+/// Takes one step of the current travel plan at a time. Travel stops as soon as a
+/// monster is in view so the player never walks blindly into a fight.
+#[allow(clippy::too_many_arguments)]
+fn travel_system(
+    time: Res<Time>,
+    mut since_last_step: Local<f32>,
+    player_query: Query<(&Position, &Viewshed), With<Player>>,
+    monster_query: Query<&Position, With<Monster>>,
+    map: Res<Map>,
+    mut pathfinding: ResMut<PathfindingCache>,
+    mut travel_plan: ResMut<TravelPlan>,
+    mut message_log: ResMut<MessageLog>,
+    mut player_move_events: EventWriter<PlayerMoveEvent>,
+) {
+    if matches!(*travel_plan, TravelPlan::Idle) {
+        *since_last_step = 0.0;
+        return;
+    }
+    *since_last_step += time.delta_seconds();
+    if *since_last_step < TRAVEL_STEP_SECONDS {
+        return;
+    }
+    *since_last_step = 0.0;
+
+    let Ok((player_pos, viewshed)) = player_query.get_single() else {
+        return;
+    };
+    let monster_in_view = monster_query.iter()
+        .any(|pos| pos.level == player_pos.level && viewshed.can_see(pos));
+    if monster_in_view {
+        message_log.add("You spot a monster and stop.".to_string(), Color::ORANGE);
+        *travel_plan = TravelPlan::Idle;
+        return;
+    }
+
+    let here = (player_pos.x, player_pos.y);
+    let next = match &mut *travel_plan {
+        TravelPlan::Idle => None,
+        TravelPlan::Travelling(path) => path.pop_front(),
+        TravelPlan::Exploring => {
+            let next = map.get_level(player_pos.level).and_then(|level_map| {
+                let frontier = frontier_tiles(level_map);
+                let graph = pathfinding.graph(&map, player_pos.level)?;
+                graph.dijkstra_map(&frontier, |(x, y)| level_map.is_revealed(x, y))
+                    .next_step(here, &HashSet::new())
+            });
+            if next.is_none() {
+                message_log.add("There is nothing left to explore here.".to_string(), Color::GRAY);
+            }
+            next
+        }
+    };
+
+    match next {
+        Some((x, y)) if (x - here.0).abs() + (y - here.1).abs() == 1 => {
+            player_move_events.send(PlayerMoveEvent { dx: x - here.0, dy: y - here.1 });
+        }
+        _ => *travel_plan = TravelPlan::Idle,
+    }
+}
+
+/// Known walkable tiles that border unexplored space
+fn frontier_tiles(level_map: &LevelMap) -> Vec<(i32, i32)> {
+    let mut frontier = Vec::new();
+    for y in 0..level_map.height {
+        for x in 0..level_map.width {
+            if !level_map.is_revealed(x, y) || !level_map.is_walkable(x, y) {
+                continue;
+            }
+            let borders_unknown = [(0, 1), (0, -1), (-1, 0), (1, 0)].iter()
+                .any(|(dx, dy)| level_map.in_bounds(x + dx, y + dy) && !level_map.is_revealed(x + dx, y + dy));
+            if borders_unknown {
+                frontier.push((x, y));
+            }
+        }
+    }
+    frontier
+}
+
+fn cancel_travel(mut travel_plan: ResMut<TravelPlan>) {
+    *travel_plan = TravelPlan::Idle;
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn movement_system(
     mut move_events: EventReader<PlayerMoveEvent>,
     mut player_query: Query<&mut Position, (With<Player>, Without<Monster>)>,
     monster_query: Query<(Entity, &Position, &Name), (With<Monster>, Without<Player>)>,
+    npc_query: Query<(Entity, &Position), (With<Npc>, Without<Monster>, Without<Player>)>,
     mut message_log: ResMut<MessageLog>,
     mut next_state: ResMut<NextState<GameState>>,
     mut combat_events: EventWriter<StartCombatEvent>,
+    mut talk_events: EventWriter<TalkToNpcEvent>,
+    map: Res<Map>,
+    mut game_world: ResMut<GameWorld>,
+    mut acted_events: EventWriter<PlayerActedEvent>,
 ) {
     for event in move_events.read() {
         if let Ok(mut player_pos) = player_query.get_single_mut() {
             let new_x = player_pos.x + event.dx;
             let new_y = player_pos.y + event.dy;
+
+            // Walls block movement outright
+            if !map.is_walkable(&Position { x: new_x, y: new_y, level: player_pos.level }) {
+                continue;
+            }
+
             let mut blocked = false;
 
             // Check all monsters for collision
@@ -68,7 +265,7 @@ fn movement_system(
                    monster_pos.y == new_y && 
                    monster_pos.level == player_pos.level {
                     message_log.add(
-                        format!("You encounter {}! Press 'A' to attack.", monster_name.0),
+                        format!("You encounter {}!", monster_name.0),
                         Color::ORANGE_RED,
                     );
                     
@@ -83,9 +280,19 @@ fn movement_system(
                 }
             }
 
+            // Bumping into someone peaceful starts a conversation instead, which takes no time
+            if let Some((npc, _)) = npc_query.iter().find(|(_, npc_pos)| {
+                npc_pos.x == new_x && npc_pos.y == new_y && npc_pos.level == player_pos.level
+            }) {
+                talk_events.send(TalkToNpcEvent { npc });
+                blocked = true;
+            }
+
             if !blocked {
                 player_pos.x = new_x;
                 player_pos.y = new_y;
+                game_world.turn_count += 1;
+                acted_events.send(PlayerActedEvent);
             }
         }
     }
@@ -0,0 +1,111 @@
+// src/plugins/progression.rs
+
+use bevy::prelude::*;
+
+use crate::components::{Experience, Health, Level, Player, Stats};
+use crate::resources::MessageLog;
+
+/// Experience needed to go from level 1 to level 2; later levels cost more
+const BASE_LEVEL_XP: u32 = 50;
+/// Flat health gained every level, on top of the constitution modifier
+const HEALTH_PER_LEVEL: i32 = 5;
+/// How much each level of difference between monster and player changes the XP reward
+const XP_SCALE_PER_LEVEL: f32 = 0.2;
+/// Kill rewards never drop below or rise above these fractions of the base reward
+const MIN_XP_SCALE: f32 = 0.1;
+const MAX_XP_SCALE: f32 = 2.0;
+
+pub struct ProgressionPlugin;
+
+impl Plugin for ProgressionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<GainExperienceEvent>()
+            .add_event::<LevelUpEvent>()
+            .add_systems(Update, (award_experience_system, level_up_system).chain());
+    }
+}
+
+/// Sent whenever the player earns experience, from kills or loot
+#[derive(Event)]
+pub struct GainExperienceEvent {
+    pub amount: u32,
+}
+
+/// Sent once for every level the player gains
+#[derive(Event)]
+pub struct LevelUpEvent {
+    pub new_level: i32,
+}
+
+/// Total experience needed to advance from `level` to the next one
+pub fn experience_to_next_level(level: i32) -> u32 {
+    let level = level.max(1) as u32;
+    BASE_LEVEL_XP * level * (level + 1) / 2
+}
+
+/// Scales a monster's base experience reward by how its level compares to the
+/// player's. Tougher monsters are worth more; trivial ones are worth very little.
+pub fn kill_experience(base_reward: u32, monster_level: i32, player_level: i32) -> u32 {
+    let scale = (1.0 + (monster_level - player_level) as f32 * XP_SCALE_PER_LEVEL)
+        .clamp(MIN_XP_SCALE, MAX_XP_SCALE);
+    ((base_reward as f32 * scale).round() as u32).max(1)
+}
+
+/// The bonus a stat gives to derived values; 10 is average and gives nothing
+pub fn stat_modifier(stat: i32) -> i32 {
+    (stat - 10).div_euclid(2)
+}
+
+fn award_experience_system(
+    mut xp_events: EventReader<GainExperienceEvent>,
+    mut player_query: Query<(&mut Experience, &mut Level), With<Player>>,
+    mut level_up_events: EventWriter<LevelUpEvent>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok((mut experience, mut level)) = player_query.get_single_mut() else {
+        xp_events.clear();
+        return;
+    };
+
+    for event in xp_events.read() {
+        experience.current += event.amount;
+        message_log.add(format!("You gain {} experience.", event.amount), Color::GOLD);
+
+        // A big reward can be worth several levels at once
+        while experience.current >= experience_to_next_level(level.0) {
+            experience.current -= experience_to_next_level(level.0);
+            level.0 += 1;
+            level_up_events.send(LevelUpEvent { new_level: level.0 });
+        }
+    }
+}
+
+/// Every level raises all base stats by one and adds health from constitution.
+/// Combat stats are rederived from the new stats by the equipment plugin.
+fn level_up_system(
+    mut level_up_events: EventReader<LevelUpEvent>,
+    mut player_query: Query<(&mut Stats, &mut Health), With<Player>>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok((mut stats, mut health)) = player_query.get_single_mut() else {
+        level_up_events.clear();
+        return;
+    };
+
+    for event in level_up_events.read() {
+        stats.strength += 1;
+        stats.dexterity += 1;
+        stats.intelligence += 1;
+        stats.constitution += 1;
+
+        let health_gain = (HEALTH_PER_LEVEL + stat_modifier(stats.constitution)).max(1);
+        health.max += health_gain;
+        health.current += health_gain;
+
+        message_log.add(
+            format!("You reach level {}! You feel stronger (+{} max HP).", event.new_level, health_gain),
+            Color::GOLD,
+        );
+    }
+}
@@ -0,0 +1,414 @@
+// src/plugins/quest.rs
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{Inventory, Item, Level, Luck, Monster, Npc, Player, Position, Purse};
+use crate::game_state::GameState;
+use crate::plugins::combat::PlayerKilledMonsterEvent;
+use crate::plugins::faction::ReputationChangeEvent;
+use crate::plugins::npc::TalkToNpcEvent;
+use crate::plugins::progression::GainExperienceEvent;
+use crate::resources::{GameRng, GameWorld, MessageLog};
+use crate::systems::item_spawning::{spawn_carried_item, spawn_item_from_template};
+use crate::templates::item_templates::{ItemTemplateRef, ItemTemplateRegistry};
+use crate::templates::loot_tables::{LootResult, LootTableRegistry};
+use crate::templates::monster_templates::MonsterTemplateRegistry;
+use crate::templates::npc_templates::NPCTemplateRegistry;
+use crate::templates::quest_templates::{QuestObjective, QuestTemplate, QuestTemplateRegistry};
+
+pub struct QuestPlugin;
+
+impl Plugin for QuestPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<AcceptQuestEvent>()
+            .add_event::<TurnInQuestEvent>()
+            .init_resource::<QuestJournal>()
+            .add_systems(
+                Update,
+                (accept_quest_system, turn_in_quest_system)
+                    .chain()
+                    .run_if(in_state(GameState::Exploring))
+            )
+            // Kills in a fight count as much as anything done exploring
+            .add_systems(
+                Update,
+                (journal_input_system, track_kills, track_fetches, track_depth, track_talks)
+                    .run_if(in_state(GameState::Exploring).or_else(in_state(GameState::InCombat)))
+            );
+    }
+}
+
+/// Sent when the player takes on a quest from the NPC offering it
+#[derive(Event)]
+pub struct AcceptQuestEvent {
+    pub giver: Entity,
+    pub quest_id: String,
+}
+
+/// Sent when the player hands a finished quest back in to the NPC who gave it
+#[derive(Event)]
+pub struct TurnInQuestEvent {
+    pub giver: Entity,
+    pub quest_id: String,
+}
+
+/// Whether the quest journal window is showing
+#[derive(Resource, Default)]
+pub struct QuestJournal {
+    pub open: bool,
+}
+
+/// The quests the player has taken on and the ones they've finished
+#[derive(Component, Clone, Default, Serialize, Deserialize)]
+pub struct QuestLog {
+    pub active: Vec<ActiveQuest>,
+    /// IDs of quests handed in, oldest first
+    pub completed: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ActiveQuest {
+    pub quest_id: String,
+    /// Progress on each of the quest's objectives, in order
+    pub progress: Vec<u32>,
+}
+
+impl QuestLog {
+    pub fn active(&self, quest_id: &str) -> Option<&ActiveQuest> {
+        self.active.iter().find(|quest| quest.quest_id == quest_id)
+    }
+
+    pub fn is_completed(&self, quest_id: &str) -> bool {
+        self.completed.iter().any(|completed| completed == quest_id)
+    }
+}
+
+impl ActiveQuest {
+    pub fn objective_done(&self, template: &QuestTemplate, index: usize) -> bool {
+        let progress = self.progress.get(index).copied().unwrap_or(0);
+        template.objectives.get(index).is_some_and(|objective| progress >= objective.required())
+    }
+
+    /// Every objective is done and the quest can be handed in
+    pub fn is_done(&self, template: &QuestTemplate) -> bool {
+        (0..template.objectives.len()).all(|index| self.objective_done(template, index))
+    }
+}
+
+/// Whether an NPC hands out a quest, so it can be taken from and returned to them
+fn offers_quest(registry: &NPCTemplateRegistry, npc: &Npc, quest_id: &str) -> bool {
+    registry.get(&npc.template_id)
+        .is_some_and(|template| template.quest_ids().iter().any(|offered| offered == quest_id))
+}
+
+/// How many of an item the player has in their pack, over every stack
+fn carried_count(inventory: &Inventory, item_query: &Query<(&mut Item, &ItemTemplateRef)>, item_id: &str) -> u32 {
+    inventory.items.iter()
+        .filter_map(|&carried| item_query.get(carried).ok())
+        .filter(|(_, template_ref)| template_ref.0 == item_id)
+        .map(|(item, _)| item.stack_size)
+        .sum()
+}
+
+/// Sets the quest's fetch objectives to what's in the pack right now, however it
+/// got there. Returns whether anything changed.
+fn sync_fetches(
+    quest: &mut ActiveQuest,
+    template: &QuestTemplate,
+    inventory: &Inventory,
+    item_query: &Query<(&mut Item, &ItemTemplateRef)>,
+) -> bool {
+    let mut changed = false;
+    for (objective, progress) in template.objectives.iter().zip(quest.progress.iter_mut()) {
+        if let QuestObjective::Fetch { item_id, count } = objective {
+            let carried = carried_count(inventory, item_query, item_id).min(*count);
+            if *progress != carried {
+                *progress = carried;
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// Adds progress to every active objective `progress` gives some for, and says
+/// so when that finishes an objective or a whole quest. `progress` can also
+/// return the full amount needed, for objectives that are simply met or not.
+fn record_progress(
+    log: &mut QuestLog,
+    quests: &QuestTemplateRegistry,
+    message_log: &mut MessageLog,
+    progress: impl Fn(&QuestObjective) -> u32,
+) {
+    for quest in log.active.iter_mut() {
+        let Some(template) = quests.get(&quest.quest_id) else {
+            continue;
+        };
+        let was_done = quest.is_done(template);
+        for (index, objective) in template.objectives.iter().enumerate() {
+            let gained = progress(objective);
+            let Some(current) = quest.progress.get_mut(index) else {
+                continue;
+            };
+            if gained == 0 || *current >= objective.required() {
+                continue;
+            }
+            *current = (*current + gained).min(objective.required());
+            if *current >= objective.required() && template.objectives.len() > 1 {
+                message_log.add(format!("{}: objective complete.", template.name), Color::GOLD);
+            }
+        }
+        if !was_done && quest.is_done(template) {
+            message_log.add(format!("{} is ready to hand in.", template.name), Color::GOLD);
+        }
+    }
+}
+
+fn journal_input_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut journal: ResMut<QuestJournal>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyJ) {
+        journal.open = !journal.open;
+    }
+}
+
+fn accept_quest_system(
+    mut accept_events: EventReader<AcceptQuestEvent>,
+    mut player_query: Query<(&Inventory, &mut QuestLog), With<Player>>,
+    item_query: Query<(&mut Item, &ItemTemplateRef)>,
+    npc_query: Query<&Npc, Without<Monster>>,
+    quests: Res<QuestTemplateRegistry>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok((inventory, mut log)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for event in accept_events.read() {
+        let Some(template) = quests.get(&event.quest_id) else {
+            continue;
+        };
+        let offered = npc_query.get(event.giver).is_ok_and(|npc| offers_quest(&npc_registry, npc, &event.quest_id));
+        if !offered || log.active(&event.quest_id).is_some() || log.is_completed(&event.quest_id) {
+            continue;
+        }
+
+        // Things already in the pack count towards fetching
+        let progress = template.objectives.iter()
+            .map(|objective| match objective {
+                QuestObjective::Fetch { item_id, count } => carried_count(inventory, &item_query, item_id).min(*count),
+                _ => 0,
+            })
+            .collect();
+        log.active.push(ActiveQuest { quest_id: template.id.clone(), progress });
+        message_log.add(format!("New quest: {}.", template.name), Color::GOLD);
+    }
+}
+
+/// Takes the fetched items, pays out the rewards and files the quest as done
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn turn_in_quest_system(
+    mut commands: Commands,
+    mut turn_in_events: EventReader<TurnInQuestEvent>,
+    mut player_query: Query<(&Position, &Level, Option<&Luck>, &mut Purse, &mut Inventory, &mut QuestLog), With<Player>>,
+    mut item_query: Query<(&mut Item, &ItemTemplateRef)>,
+    npc_query: Query<&Npc, Without<Monster>>,
+    quests: Res<QuestTemplateRegistry>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    item_registry: Res<ItemTemplateRegistry>,
+    loot_tables: Res<LootTableRegistry>,
+    mut rng: ResMut<GameRng>,
+    mut xp_events: EventWriter<GainExperienceEvent>,
+    mut reputation_events: EventWriter<ReputationChangeEvent>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok((position, level, luck, mut purse, mut inventory, mut log)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for event in turn_in_events.read() {
+        let Some(template) = quests.get(&event.quest_id) else {
+            continue;
+        };
+        let offered = npc_query.get(event.giver).is_ok_and(|npc| offers_quest(&npc_registry, npc, &event.quest_id));
+        let Some(index) = log.active.iter().position(|quest| quest.quest_id == event.quest_id) else {
+            continue;
+        };
+        if !offered {
+            continue;
+        }
+        // Whatever was fetched may since have been dropped, sold or used up
+        sync_fetches(&mut log.active[index], template, &inventory, &item_query);
+        if !log.active[index].is_done(template) {
+            continue;
+        }
+
+        for objective in &template.objectives {
+            let QuestObjective::Fetch { item_id, count } = objective else {
+                continue;
+            };
+            let mut owed = *count;
+            inventory.items.retain(|&carried| {
+                let Ok((mut item, template_ref)) = item_query.get_mut(carried) else {
+                    return true;
+                };
+                if owed == 0 || template_ref.0 != *item_id {
+                    return true;
+                }
+                let taken = item.stack_size.min(owed);
+                owed -= taken;
+                item.stack_size -= taken;
+                if item.stack_size > 0 {
+                    return true;
+                }
+                commands.entity(carried).despawn_recursive();
+                false
+            });
+        }
+
+        log.active.remove(index);
+        log.completed.push(template.id.clone());
+        message_log.add(format!("Quest complete: {}!", template.name), Color::GOLD);
+
+        let rewards = &template.rewards;
+        let mut gold = rewards.gold;
+        let mut experience = rewards.experience;
+        let luck = luck.map_or(0.0, |luck| luck.0);
+        for table_id in &rewards.loot_tables {
+            for result in loot_tables.roll_loot(table_id, level.0.max(1) as u32, luck, &mut *rng) {
+                match result {
+                    LootResult::Item { item_id, quantity } => {
+                        let Some(name) = item_registry.get(&item_id).map(|item| item.name().to_string()) else {
+                            continue;
+                        };
+                        if inventory.is_full() {
+                            spawn_item_from_template(&mut commands, &item_registry, &item_id, *position, quantity);
+                            message_log.add(format!("Your pack is full; {} is left at your feet.", name), Color::ORANGE);
+                        } else if let Some(reward) = spawn_carried_item(&mut commands, &item_registry, &item_id, quantity) {
+                            inventory.items.push(reward);
+                            message_log.add(format!("You receive {}.", name), Color::LIME_GREEN);
+                        }
+                    }
+                    LootResult::Gold { amount } => gold += amount,
+                    LootResult::Experience { amount } => experience += amount,
+                }
+            }
+        }
+        if gold > 0 {
+            purse.gold += gold;
+            message_log.add(format!("You receive {} gold.", gold), Color::GOLD);
+        }
+        if experience > 0 {
+            xp_events.send(GainExperienceEvent { amount: experience });
+        }
+        for (faction, &amount) in &rewards.reputation {
+            reputation_events.send(ReputationChangeEvent { faction: faction.clone(), amount });
+        }
+    }
+}
+
+fn track_kills(
+    mut kill_events: EventReader<PlayerKilledMonsterEvent>,
+    mut player_query: Query<&mut QuestLog, With<Player>>,
+    monster_templates: Res<MonsterTemplateRegistry>,
+    quests: Res<QuestTemplateRegistry>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok(mut log) = player_query.get_single_mut() else {
+        return;
+    };
+    for event in kill_events.read() {
+        let Some(monster) = event.template_id.as_deref().and_then(|template_id| monster_templates.get(template_id)) else {
+            continue;
+        };
+        record_progress(&mut log, &quests, &mut message_log, |objective| match objective {
+            QuestObjective::Kill { monster_type, .. }
+                if *monster_type == monster.monster_type || *monster_type == monster.id => 1,
+            _ => 0,
+        });
+    }
+}
+
+/// Fetch objectives follow what's in the pack, whether it was picked up, bought,
+/// stolen or given, and go back down when it's dropped, sold or used up
+fn track_fetches(
+    mut player_query: Query<(&Inventory, &mut QuestLog), With<Player>>,
+    item_query: Query<(&mut Item, &ItemTemplateRef)>,
+    quests: Res<QuestTemplateRegistry>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok((inventory, mut log)) = player_query.get_single_mut() else {
+        return;
+    };
+    // Only mark the log changed when something did, rather than every frame
+    let mut changed = false;
+    for quest in log.bypass_change_detection().active.iter_mut() {
+        let Some(template) = quests.get(&quest.quest_id) else {
+            continue;
+        };
+        let was_done = quest.is_done(template);
+        if sync_fetches(quest, template, inventory, &item_query) {
+            changed = true;
+            if !was_done && quest.is_done(template) {
+                message_log.add(format!("{} is ready to hand in.", template.name), Color::GOLD);
+            }
+        }
+    }
+    if changed {
+        log.set_changed();
+    }
+}
+
+/// Checked every frame rather than on the stairs, so a quest for a depth the
+/// player has already reached is met as soon as it's taken on
+fn track_depth(
+    mut player_query: Query<&mut QuestLog, With<Player>>,
+    game_world: Res<GameWorld>,
+    quests: Res<QuestTemplateRegistry>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok(mut log) = player_query.get_single_mut() else {
+        return;
+    };
+    let depth_pending = log.active.iter().any(|quest| {
+        quests.get(&quest.quest_id).is_some_and(|template| {
+            template.objectives.iter().enumerate().any(|(index, objective)| {
+                matches!(objective, QuestObjective::ReachDepth { depth } if game_world.dungeon_level >= *depth)
+                    && !quest.objective_done(template, index)
+            })
+        })
+    });
+    // Only touch the log when there's something to record, so it isn't marked changed every frame
+    if !depth_pending {
+        return;
+    }
+    record_progress(&mut log, &quests, &mut message_log, |objective| match objective {
+        QuestObjective::ReachDepth { depth } if game_world.dungeon_level >= *depth => 1,
+        _ => 0,
+    });
+}
+
+fn track_talks(
+    mut talk_events: EventReader<TalkToNpcEvent>,
+    mut player_query: Query<&mut QuestLog, With<Player>>,
+    npc_query: Query<&Npc>,
+    quests: Res<QuestTemplateRegistry>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok(mut log) = player_query.get_single_mut() else {
+        return;
+    };
+    for event in talk_events.read() {
+        let Ok(npc) = npc_query.get(event.npc) else {
+            continue;
+        };
+        record_progress(&mut log, &quests, &mut message_log, |objective| match objective {
+            QuestObjective::TalkTo { npc: wanted } if *wanted == npc.template_id => 1,
+            _ => 0,
+        });
+    }
+}
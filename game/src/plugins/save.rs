@@ -0,0 +1,606 @@
+// src/plugins/save.rs
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::window::WindowCloseRequested;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::components::{CombatStats, DialogueFlags, EquipSlot, Equipment, Experience, Health, Inventory, Item, ItemCharges, ItemCooldowns, Level, Luck, Mana, Monster, Name, Npc, Player, Position, Purse, Reputation, Stats, StatusEffects, Viewshed};
+use crate::game_state::GameState;
+use crate::plugins::combat::CurrentCombat;
+use crate::plugins::faction::CalledToArms;
+use crate::plugins::quest::QuestLog;
+use crate::plugins::shop::ShopStock;
+use crate::plugins::map::{LevelMap, Map, MONSTER_VIEW_RANGE, PLAYER_VIEW_RANGE};
+use crate::plugins::monster::MonsterHome;
+use crate::resources::{GameRng, GameWorld, MessageLog};
+use crate::templates::item_templates::ItemTemplateRef;
+use crate::templates::monster_templates::MonsterTemplateRef;
+
+/// Every save file starts with these bytes followed by the format version.
+/// Bump `SAVE_VERSION` whenever `SaveData` changes shape.
+const SAVE_MAGIC: [u8; 4] = *b"ULAN";
+const SAVE_VERSION: u32 = 17;
+
+const SAVE_DIR: &str = "saves";
+const SAVE_EXTENSION: &str = "sav";
+
+/// Written when the player quits or closes the window mid-run
+pub const AUTOSAVE_SLOT: &str = "autosave";
+/// Written on demand with F5 or the Save button
+pub const QUICKSAVE_SLOT: &str = "quicksave";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<SaveGameEvent>()
+            .add_event::<LoadGameEvent>()
+            .init_resource::<SaveIndex>()
+            .add_systems(OnEnter(GameState::MainMenu), refresh_save_index)
+            .add_systems(Update, quicksave_input_system.run_if(in_state(GameState::Exploring)))
+            // PostUpdate so a save requested this frame still sees the state it was requested in,
+            // before any transition (e.g. "Quit to Menu") tears down combat.
+            .add_systems(
+                PostUpdate,
+                (
+                    save_on_window_close,
+                    save_game_system,
+                    load_game_system,
+                )
+                .chain()
+            );
+    }
+}
+
+#[derive(Event)]
+pub struct SaveGameEvent {
+    pub slot: String,
+}
+
+#[derive(Event)]
+pub struct LoadGameEvent {
+    pub slot: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveHeader {
+    magic: [u8; 4],
+    version: u32,
+    pub seed: u64,
+    pub dungeon_level: i32,
+    pub turn_count: u32,
+}
+
+/// A save file found on disk, as listed by the Load Game menu
+#[derive(Debug, Clone)]
+pub struct SaveSummary {
+    pub slot: String,
+    pub header: SaveHeader,
+    pub modified: SystemTime,
+}
+
+/// Saves available on disk, newest first. Refreshed whenever the main menu opens.
+#[derive(Resource, Default)]
+pub struct SaveIndex {
+    pub saves: Vec<SaveSummary>,
+}
+
+impl SaveIndex {
+    /// The save "Continue" should resume
+    pub fn latest(&self) -> Option<&SaveSummary> {
+        self.saves.first()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SaveData {
+    player: PlayerSave,
+    monsters: Vec<MonsterSave>,
+    /// NPCs who haven't been provoked; those who have are saved with the monsters
+    npcs: Vec<NpcSave>,
+    /// Items lying on the floor of any level
+    ground_items: Vec<GroundItemSave>,
+    /// The fight the player was in, if any
+    combat: Option<CombatSave>,
+    game_world: GameWorld,
+    message_log: MessageLog,
+    state: GameState,
+    levels: HashMap<i32, LevelMap>,
+    rng_seed: u64,
+    rng_stream_position: u128,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PlayerSave {
+    position: Position,
+    health: Health,
+    mana: Option<Mana>,
+    stats: Stats,
+    combat_stats: CombatStats,
+    level: Level,
+    experience: Experience,
+    luck: Luck,
+    purse: Purse,
+    reputation: Reputation,
+    dialogue_flags: DialogueFlags,
+    quest_log: QuestLog,
+    inventory: Option<InventorySave>,
+    equipment: Option<EquipmentSave>,
+    status_effects: StatusEffects,
+    cooldowns: ItemCooldowns,
+}
+
+/// Participants and the target are indices into `SaveData.monsters`; `None` is the player
+#[derive(Serialize, Deserialize)]
+struct CombatSave {
+    participants: Vec<Option<usize>>,
+    turn: usize,
+    target: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct InventorySave {
+    capacity: usize,
+    items: Vec<ItemSave>,
+}
+
+/// Equipped items, keyed by the slot they're worn in
+#[derive(Serialize, Deserialize)]
+struct EquipmentSave {
+    items: Vec<(EquipSlot, ItemSave)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ItemSave {
+    name: String,
+    item: Item,
+    template_id: Option<String>,
+    charges: Option<ItemCharges>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GroundItemSave {
+    position: Position,
+    item: ItemSave,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MonsterSave {
+    monster: Monster,
+    name: String,
+    level: Level,
+    position: Position,
+    health: Health,
+    stats: Stats,
+    combat_stats: CombatStats,
+    template_id: Option<String>,
+    status_effects: Option<StatusEffects>,
+    npc: Option<Npc>,
+    /// Where the monster guards; not yet chosen for monsters spawned this frame
+    home: Option<MonsterHome>,
+    /// Guards up in arms over their faction's standing, who stand down once it recovers
+    called_to_arms: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct NpcSave {
+    npc: Npc,
+    name: String,
+    level: Level,
+    position: Position,
+    health: Health,
+    stats: Stats,
+    combat_stats: CombatStats,
+    stock: Option<ShopStock>,
+}
+
+impl ItemSave {
+    fn new(item: &Item, name: &Name, template_ref: Option<&ItemTemplateRef>, charges: Option<&ItemCharges>) -> Self {
+        Self {
+            name: name.0.clone(),
+            item: item.clone(),
+            template_id: template_ref.map(|template_ref| template_ref.0.clone()),
+            charges: charges.copied(),
+        }
+    }
+
+    fn spawn<'a>(self, commands: &'a mut Commands) -> bevy::ecs::system::EntityCommands<'a> {
+        let mut item_commands = commands.spawn((self.item, Name(self.name)));
+        if let Some(template_id) = self.template_id {
+            item_commands.insert(ItemTemplateRef(template_id));
+        }
+        if let Some(charges) = self.charges {
+            item_commands.insert(charges);
+        }
+        item_commands
+    }
+}
+
+fn save_path(slot: &str) -> PathBuf {
+    PathBuf::from(SAVE_DIR).join(format!("{}.{}", slot, SAVE_EXTENSION))
+}
+
+fn write_save(slot: &str, header: &SaveHeader, data: &SaveData) -> Result<(), Box<dyn std::error::Error>> {
+    fs::create_dir_all(SAVE_DIR)?;
+
+    // Write to a temporary file first so a crash mid-write can't corrupt an existing save
+    let path = save_path(slot);
+    let tmp_path = path.with_extension("tmp");
+    {
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        bincode::serialize_into(&mut writer, header)?;
+        bincode::serialize_into(&mut writer, data)?;
+    }
+    fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
+
+fn read_header(reader: &mut BufReader<File>) -> Result<SaveHeader, Box<dyn std::error::Error>> {
+    let header: SaveHeader = bincode::deserialize_from(reader)?;
+    if header.magic != SAVE_MAGIC {
+        return Err("not a Myths of Ulan save file".into());
+    }
+    if header.version != SAVE_VERSION {
+        return Err(format!(
+            "save format version {} is not supported (expected {})",
+            header.version, SAVE_VERSION
+        ).into());
+    }
+    Ok(header)
+}
+
+fn read_save(slot: &str) -> Result<SaveData, Box<dyn std::error::Error>> {
+    let mut reader = BufReader::new(File::open(save_path(slot))?);
+    read_header(&mut reader)?;
+    Ok(bincode::deserialize_from(&mut reader)?)
+}
+
+/// Lists every readable save in the save directory, newest first
+pub fn list_saves() -> Vec<SaveSummary> {
+    let Ok(entries) = fs::read_dir(SAVE_DIR) else {
+        return Vec::new();
+    };
+
+    let mut saves: Vec<SaveSummary> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == SAVE_EXTENSION))
+        .filter_map(|path| {
+            let slot = path.file_stem()?.to_str()?.to_string();
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+            let mut reader = BufReader::new(File::open(&path).ok()?);
+            match read_header(&mut reader) {
+                Ok(header) => Some(SaveSummary { slot, header, modified }),
+                Err(e) => {
+                    warn!("Skipping save {:?}: {}", path, e);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    saves.sort_by_key(|save| std::cmp::Reverse(save.modified));
+    saves
+}
+
+fn refresh_save_index(mut save_index: ResMut<SaveIndex>) {
+    save_index.saves = list_saves();
+}
+
+fn quicksave_input_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut save_events: EventWriter<SaveGameEvent>,
+) {
+    if keyboard.just_pressed(KeyCode::F5) {
+        save_events.send(SaveGameEvent { slot: QUICKSAVE_SLOT.to_string() });
+    }
+}
+
+/// The window is configured not to close by itself, so the run can be saved first
+fn save_on_window_close(
+    mut close_events: EventReader<WindowCloseRequested>,
+    state: Res<State<GameState>>,
+    mut save_events: EventWriter<SaveGameEvent>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    if close_events.read().last().is_none() {
+        return;
+    }
+
+    if matches!(state.get(), GameState::Exploring | GameState::InCombat) {
+        save_events.send(SaveGameEvent { slot: AUTOSAVE_SLOT.to_string() });
+    }
+    exit_events.send(AppExit);
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn save_game_system(
+    mut save_events: EventReader<SaveGameEvent>,
+    player_query: Query<(&Position, &Health, Option<&Mana>, &Stats, &CombatStats, &Level, &Experience, Option<&Luck>, Option<&Purse>, (Option<&Reputation>, Option<&DialogueFlags>, Option<&QuestLog>), Option<&Inventory>, Option<&Equipment>, Option<&StatusEffects>, Option<&ItemCooldowns>), With<Player>>,
+    monster_query: Query<(Entity, &Monster, &Name, &Level, &Position, &Health, &Stats, &CombatStats, Option<&MonsterTemplateRef>, Option<&StatusEffects>, Option<&Npc>, Option<&MonsterHome>, Has<CalledToArms>)>,
+    npc_query: Query<(&Npc, &Name, &Level, &Position, &Health, &Stats, &CombatStats, Option<&ShopStock>), Without<Monster>>,
+    item_query: Query<(&Item, &Name, Option<&ItemTemplateRef>, Option<&ItemCharges>)>,
+    ground_item_query: Query<(&Item, &Name, &Position, Option<&ItemTemplateRef>, Option<&ItemCharges>)>,
+    game_world: Res<GameWorld>,
+    mut message_log: ResMut<MessageLog>,
+    state: Res<State<GameState>>,
+    current_combat: Res<CurrentCombat>,
+    map: Res<Map>,
+    rng: Res<GameRng>,
+) {
+    for event in save_events.read() {
+        let Ok((position, health, mana, stats, combat_stats, level, experience, luck, purse, (reputation, dialogue_flags, quest_log), inventory, equipment, status_effects, cooldowns)) = player_query.get_single() else {
+            warn!("Nothing to save: no player in the world");
+            continue;
+        };
+
+        let inventory = inventory.map(|inventory| InventorySave {
+            capacity: inventory.capacity,
+            items: inventory.items
+                .iter()
+                .filter_map(|&entity| item_query.get(entity).ok())
+                .map(|(item, name, template_ref, charges)| ItemSave::new(item, name, template_ref, charges))
+                .collect(),
+        });
+
+        let equipment = equipment.map(|equipment| EquipmentSave {
+            items: [EquipSlot::Weapon, EquipSlot::Armor, EquipSlot::Shield]
+                .into_iter()
+                .filter_map(|slot| {
+                    let (item, name, template_ref, charges) = item_query.get(equipment.slot(slot)?).ok()?;
+                    Some((slot, ItemSave::new(item, name, template_ref, charges)))
+                })
+                .collect(),
+        });
+
+        let ground_items = ground_item_query.iter()
+            .map(|(item, name, position, template_ref, charges)| GroundItemSave {
+                position: *position,
+                item: ItemSave::new(item, name, template_ref, charges),
+            })
+            .collect();
+
+        let mut monster_indices = HashMap::new();
+        let mut monsters = Vec::new();
+        for (entity, monster, name, level, position, health, stats, combat_stats, template_ref, status_effects, npc, home, called_to_arms) in monster_query.iter() {
+            monster_indices.insert(entity, monsters.len());
+            monsters.push(MonsterSave {
+                monster: monster.clone(),
+                name: name.0.clone(),
+                level: *level,
+                position: *position,
+                health: health.clone(),
+                stats: stats.clone(),
+                combat_stats: combat_stats.clone(),
+                template_id: template_ref.map(|template_ref| template_ref.0.clone()),
+                status_effects: status_effects.cloned(),
+                npc: npc.cloned(),
+                home: home.copied(),
+                called_to_arms,
+            });
+        }
+
+        let npcs = npc_query.iter()
+            .map(|(npc, name, level, position, health, stats, combat_stats, stock)| NpcSave {
+                npc: npc.clone(),
+                name: name.0.clone(),
+                level: *level,
+                position: *position,
+                health: health.clone(),
+                stats: stats.clone(),
+                combat_stats: combat_stats.clone(),
+                stock: stock.cloned(),
+            })
+            .collect();
+
+        let combat = (!current_combat.participants.is_empty()).then(|| CombatSave {
+            participants: current_combat.participants
+                .iter()
+                .map(|entity| monster_indices.get(entity).copied())
+                .collect(),
+            turn: current_combat.turn,
+            target: current_combat.target.and_then(|entity| monster_indices.get(&entity).copied()),
+        });
+
+        let header = SaveHeader {
+            magic: SAVE_MAGIC,
+            version: SAVE_VERSION,
+            seed: rng.seed(),
+            dungeon_level: game_world.dungeon_level,
+            turn_count: game_world.turn_count,
+        };
+
+        let data = SaveData {
+            player: PlayerSave {
+                position: *position,
+                health: health.clone(),
+                mana: mana.cloned(),
+                stats: stats.clone(),
+                combat_stats: combat_stats.clone(),
+                level: *level,
+                experience: *experience,
+                luck: luck.copied().unwrap_or_default(),
+                purse: purse.copied().unwrap_or_default(),
+                reputation: reputation.cloned().unwrap_or_default(),
+                dialogue_flags: dialogue_flags.cloned().unwrap_or_default(),
+                quest_log: quest_log.cloned().unwrap_or_default(),
+                inventory,
+                equipment,
+                status_effects: status_effects.cloned().unwrap_or_default(),
+                cooldowns: cooldowns.cloned().unwrap_or_default(),
+            },
+            monsters,
+            npcs,
+            ground_items,
+            combat,
+            game_world: game_world.clone(),
+            message_log: message_log.clone(),
+            state: *state.get(),
+            levels: map.levels.clone(),
+            rng_seed: rng.seed(),
+            rng_stream_position: rng.stream_position(),
+        };
+
+        match write_save(&event.slot, &header, &data) {
+            Ok(()) => {
+                info!("Saved game to slot '{}'", event.slot);
+                message_log.add(format!("Game saved ({}).", event.slot), Color::LIME_GREEN);
+            }
+            Err(e) => {
+                error!("Failed to save game to slot '{}': {}", event.slot, e);
+                message_log.add(format!("Failed to save game: {}", e), Color::RED);
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn load_game_system(
+    mut commands: Commands,
+    mut load_events: EventReader<LoadGameEvent>,
+    entities: Query<Entity, Or<(With<Player>, With<Monster>, With<Npc>, With<Item>)>>,
+    mut map: ResMut<Map>,
+    mut game_world: ResMut<GameWorld>,
+    mut message_log: ResMut<MessageLog>,
+    mut rng: ResMut<GameRng>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut current_combat: ResMut<CurrentCombat>,
+) {
+    // Only the most recent request matters
+    let Some(event) = load_events.read().last() else {
+        return;
+    };
+
+    let data = match read_save(&event.slot) {
+        Ok(data) => data,
+        Err(e) => {
+            error!("Failed to load save '{}': {}", event.slot, e);
+            message_log.add(format!("Failed to load game: {}", e), Color::RED);
+            return;
+        }
+    };
+
+    // Clear out whatever run was in progress
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let player = data.player;
+    let mut player_commands = commands.spawn((
+        Player,
+        player.position,
+        player.health,
+        player.stats,
+        player.combat_stats,
+        player.level,
+        player.experience,
+        player.luck,
+        player.purse,
+        player.reputation,
+        player.dialogue_flags,
+        player.status_effects,
+        player.cooldowns,
+        Viewshed::new(PLAYER_VIEW_RANGE),
+        Name("Player".to_string()),
+    ));
+    if let Some(inventory) = player.inventory {
+        let items = inventory.items
+            .into_iter()
+            .map(|saved| saved.spawn(&mut player_commands.commands()).id())
+            .collect();
+        player_commands.insert(Inventory { items, capacity: inventory.capacity });
+    }
+    if let Some(saved_equipment) = player.equipment {
+        let mut equipment = Equipment::default();
+        for (slot, saved) in saved_equipment.items {
+            *equipment.slot_mut(slot) = Some(saved.spawn(&mut player_commands.commands()).id());
+        }
+        player_commands.insert(equipment);
+    }
+    if let Some(mana) = player.mana {
+        player_commands.insert(mana);
+    }
+    player_commands.insert(player.quest_log);
+    let player_entity = player_commands.id();
+
+    for saved in data.ground_items {
+        saved.item.spawn(&mut commands).insert(saved.position);
+    }
+
+    let mut monster_entities = Vec::new();
+    for saved in data.monsters {
+        let mut monster_commands = commands.spawn((
+            saved.monster,
+            Name(saved.name),
+            saved.level,
+            saved.position,
+            saved.health,
+            saved.stats,
+            saved.combat_stats,
+            Viewshed::new(MONSTER_VIEW_RANGE),
+        ));
+        if let Some(template_id) = saved.template_id {
+            monster_commands.insert(MonsterTemplateRef(template_id));
+        }
+        if let Some(status_effects) = saved.status_effects {
+            monster_commands.insert(status_effects);
+        }
+        if let Some(npc) = saved.npc {
+            monster_commands.insert(npc);
+        }
+        if let Some(home) = saved.home {
+            monster_commands.insert(home);
+        }
+        if saved.called_to_arms {
+            monster_commands.insert(CalledToArms);
+        }
+        monster_entities.push(monster_commands.id());
+    }
+
+    for saved in data.npcs {
+        let mut npc_commands = commands.spawn((
+            saved.npc,
+            Name(saved.name),
+            saved.level,
+            saved.position,
+            saved.health,
+            saved.stats,
+            saved.combat_stats,
+        ));
+        if let Some(stock) = saved.stock {
+            npc_commands.insert(stock);
+        }
+    }
+
+    // Pick the fight back up exactly where it was, turn order and all
+    *current_combat = match data.combat {
+        Some(combat) => CurrentCombat {
+            participants: combat.participants
+                .into_iter()
+                .filter_map(|index| match index {
+                    Some(index) => monster_entities.get(index).copied(),
+                    None => Some(player_entity),
+                })
+                .collect(),
+            turn: combat.turn,
+            target: combat.target.and_then(|index| monster_entities.get(index).copied()),
+            last_attacker: None,
+        },
+        None => CurrentCombat::default(),
+    };
+
+    map.levels = data.levels;
+    *game_world = data.game_world;
+    *message_log = data.message_log;
+    *rng = GameRng::restore(data.rng_seed, data.rng_stream_position);
+
+    message_log.add("Game loaded. Welcome back!".to_string(), Color::LIME_GREEN);
+    info!("Loaded game from slot '{}'", event.slot);
+    next_state.set(data.state);
+}
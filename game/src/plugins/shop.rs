@@ -0,0 +1,420 @@
+// src/plugins/shop.rs
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{Inventory, Item, Level, Monster, Name, Npc, Player, Purse, Reputation, Stats};
+use crate::game_state::GameState;
+use crate::plugins::faction::{ReputationChangeEvent, HOSTILE_REPUTATION};
+use crate::plugins::npc::NpcDialog;
+use crate::plugins::player::PlayerActedEvent;
+use crate::plugins::progression::stat_modifier;
+use crate::resources::{GameRng, GameWorld, MessageLog};
+use crate::systems::item_spawning::spawn_carried_item;
+use crate::templates::item_templates::{ItemRarity, ItemTemplate, ItemTemplateRef, ItemTemplateRegistry};
+use crate::templates::loot_tables::{LootResult, LootTableRegistry};
+use crate::templates::npc_templates::NPCTemplateRegistry;
+
+/// Turns before a merchant's shelves are filled afresh
+const RESTOCK_TURNS: u32 = 500;
+/// Share of an item's price merchants pay when buying it from the player
+const SELL_FRACTION: f32 = 0.4;
+/// Prices drop by this much for every point of reputation with the merchant's
+/// faction, and rise as much for every point below zero
+const REPUTATION_PRICE_STEP: f32 = 0.0025;
+/// However the player stands with a faction, prices stay within these bounds
+const MIN_REPUTATION_MULTIPLIER: f32 = 0.75;
+const MAX_REPUTATION_MULTIPLIER: f32 = 1.25;
+/// Chance in percent of lifting an item unseen, before the thief's dexterity
+const STEAL_BASE_CHANCE: i32 = 35;
+/// Added to the chance for each point of dexterity modifier
+const STEAL_CHANCE_PER_MODIFIER: i32 = 6;
+/// However nimble or clumsy the thief, the odds stay within these bounds
+const MIN_STEAL_CHANCE: i32 = 5;
+const MAX_STEAL_CHANCE: i32 = 80;
+/// Standing lost with a merchant's faction for being caught stealing from them
+const THEFT_REPUTATION: i32 = -20;
+
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<OpenShopEvent>()
+            .add_event::<BuyItemEvent>()
+            .add_event::<SellItemEvent>()
+            .add_event::<StealItemEvent>()
+            .init_resource::<ShopWindow>()
+            .add_systems(
+                Update,
+                (open_shop_system, buy_item_system, sell_item_system, steal_item_system, close_shop_with_dialog)
+                    .chain()
+                    .run_if(in_state(GameState::Exploring))
+            )
+            .add_systems(OnExit(GameState::Exploring), close_shop);
+    }
+}
+
+/// Sent when the player asks a merchant to trade
+#[derive(Event)]
+pub struct OpenShopEvent {
+    pub merchant: Entity,
+}
+
+/// Buys one of an item the merchant has in stock
+#[derive(Event)]
+pub struct BuyItemEvent {
+    pub merchant: Entity,
+    pub item_id: String,
+}
+
+/// Sells one item from a stack in the player's pack to the merchant
+#[derive(Event)]
+pub struct SellItemEvent {
+    pub merchant: Entity,
+    pub item: Entity,
+}
+
+/// Tries to pocket one of an item the merchant has in stock without paying
+#[derive(Event)]
+pub struct StealItemEvent {
+    pub merchant: Entity,
+    pub item_id: String,
+}
+
+/// The merchant whose shop window is open, if any
+#[derive(Resource, Default)]
+pub struct ShopWindow {
+    pub merchant: Option<Entity>,
+}
+
+/// What a merchant has for sale, rolled from their shop's loot table
+#[derive(Component, Clone, Default, Serialize, Deserialize)]
+pub struct ShopStock {
+    pub items: Vec<StockEntry>,
+    /// Turn the shelves were last filled
+    pub restocked_at: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StockEntry {
+    pub item_id: String,
+    pub quantity: u32,
+}
+
+impl ShopStock {
+    fn add(&mut self, item_id: &str, quantity: u32) {
+        match self.items.iter_mut().find(|entry| entry.item_id == item_id) {
+            Some(entry) => entry.quantity += quantity,
+            None => self.items.push(StockEntry { item_id: item_id.to_string(), quantity }),
+        }
+    }
+
+    /// Takes one of an item off the shelf. Returns false if there is none left.
+    fn take(&mut self, item_id: &str) -> bool {
+        let Some(index) = self.items.iter().position(|entry| entry.item_id == item_id && entry.quantity > 0) else {
+            return false;
+        };
+        self.items[index].quantity -= 1;
+        if self.items[index].quantity == 0 {
+            self.items.remove(index);
+        }
+        true
+    }
+}
+
+pub fn rarity_multiplier(rarity: &ItemRarity) -> f32 {
+    match rarity {
+        ItemRarity::Common => 1.0,
+        ItemRarity::Uncommon => 1.5,
+        ItemRarity::Rare => 2.5,
+        ItemRarity::Epic => 4.0,
+        ItemRarity::Legendary => 6.0,
+    }
+}
+
+/// Well-liked customers pay less and get more for what they sell
+pub fn reputation_multiplier(reputation: i32) -> f32 {
+    (1.0 - reputation as f32 * REPUTATION_PRICE_STEP).clamp(MIN_REPUTATION_MULTIPLIER, MAX_REPUTATION_MULTIPLIER)
+}
+
+/// What a merchant charges for one of an item
+pub fn buy_price(template: &ItemTemplate, reputation: i32) -> u32 {
+    let price = template.value() as f32 * rarity_multiplier(&template.rarity()) * reputation_multiplier(reputation);
+    (price.round() as u32).max(1)
+}
+
+/// What a merchant pays for one of an item. Worthless items fetch nothing.
+pub fn sell_price(template: &ItemTemplate, reputation: i32) -> u32 {
+    let price = template.value() as f32 * rarity_multiplier(&template.rarity()) * SELL_FRACTION / reputation_multiplier(reputation);
+    price.floor() as u32
+}
+
+/// The player's standing with the faction a merchant belongs to
+pub fn merchant_reputation(registry: &NPCTemplateRegistry, npc: &Npc, reputation: Option<&Reputation>) -> i32 {
+    let faction = registry.get(&npc.template_id).and_then(|template| template.faction.as_deref());
+    match (faction, reputation) {
+        (Some(faction), Some(reputation)) => reputation.with(faction),
+        _ => 0,
+    }
+}
+
+/// Chance in percent of stealing from a merchant without being caught
+pub fn steal_chance(stats: &Stats) -> i32 {
+    (STEAL_BASE_CHANCE + stat_modifier(stats.dexterity) * STEAL_CHANCE_PER_MODIFIER).clamp(MIN_STEAL_CHANCE, MAX_STEAL_CHANCE)
+}
+
+/// Puts one of an item in the player's pack, on a stack already there if it
+/// has room. Returns false if there's nowhere to put it.
+fn stow_item(
+    commands: &mut Commands,
+    inventory: &mut Inventory,
+    item_query: &mut Query<(&mut Item, Option<&ItemTemplateRef>)>,
+    item_registry: &ItemTemplateRegistry,
+    template: &ItemTemplate,
+) -> bool {
+    let stack = inventory.items.iter().copied().find(|&carried| {
+        item_query.get(carried).is_ok_and(|(item, template_ref)| {
+            template_ref.is_some_and(|template_ref| template_ref.0 == template.id())
+                && item.stack_size < template.max_stack()
+        })
+    });
+    match stack {
+        Some(carried) => {
+            if let Ok((mut item, _)) = item_query.get_mut(carried) {
+                item.stack_size += 1;
+            }
+            true
+        }
+        None if inventory.is_full() => false,
+        None => match spawn_carried_item(commands, item_registry, template.id(), 1) {
+            Some(stowed) => {
+                inventory.items.push(stowed);
+                true
+            }
+            None => false,
+        },
+    }
+}
+
+/// Opens a merchant's shop, filling the shelves first if they're empty or the
+/// stock is due to be replaced. Merchants won't deal with enemies of their faction.
+#[allow(clippy::too_many_arguments)]
+fn open_shop_system(
+    mut commands: Commands,
+    mut open_events: EventReader<OpenShopEvent>,
+    mut merchant_query: Query<(&Npc, &Name, Option<&mut ShopStock>), Without<Monster>>,
+    player_query: Query<(&Level, Option<&Reputation>), With<Player>>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    loot_tables: Res<LootTableRegistry>,
+    game_world: Res<GameWorld>,
+    mut rng: ResMut<GameRng>,
+    mut shop_window: ResMut<ShopWindow>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Some(event) = open_events.read().last() else {
+        return;
+    };
+    let Ok((npc, name, stock)) = merchant_query.get_mut(event.merchant) else {
+        return;
+    };
+    let Some(table_id) = npc_registry.get(&npc.template_id).and_then(|template| template.shop_table()) else {
+        message_log.add(format!("{} has nothing to sell.", name.0), Color::GRAY);
+        return;
+    };
+    let reputation = player_query.get_single().ok().and_then(|(_, reputation)| reputation);
+    if merchant_reputation(&npc_registry, npc, reputation) <= HOSTILE_REPUTATION {
+        message_log.add(format!("{} won't trade with the likes of you.", name.0), Color::ORANGE);
+        return;
+    }
+
+    let turn = game_world.turn_count;
+    let due = stock.as_ref().is_none_or(|stock| turn >= stock.restocked_at + RESTOCK_TURNS);
+    if due {
+        // Better customers find better wares
+        let player_level = player_query.get_single().map_or(1, |(level, _)| level.0);
+        let mut fresh = ShopStock { items: Vec::new(), restocked_at: turn };
+        for result in loot_tables.roll_loot(table_id, player_level.max(1) as u32, 0.0, &mut *rng) {
+            if let LootResult::Item { item_id, quantity } = result {
+                fresh.add(&item_id, quantity);
+            }
+        }
+        match stock {
+            Some(mut stock) => {
+                *stock = fresh;
+                message_log.add(format!("{} has fresh stock in.", name.0), Color::GOLD);
+            }
+            None => {
+                commands.entity(event.merchant).insert(fresh);
+            }
+        }
+    }
+
+    shop_window.merchant = Some(event.merchant);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn buy_item_system(
+    mut commands: Commands,
+    mut buy_events: EventReader<BuyItemEvent>,
+    mut player_query: Query<(&mut Inventory, &mut Purse, Option<&Reputation>), With<Player>>,
+    mut merchant_query: Query<(&Npc, &Name, &mut ShopStock), Without<Monster>>,
+    mut item_query: Query<(&mut Item, Option<&ItemTemplateRef>)>,
+    item_registry: Res<ItemTemplateRegistry>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok((mut inventory, mut purse, reputation)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for event in buy_events.read() {
+        let Ok((npc, merchant_name, mut stock)) = merchant_query.get_mut(event.merchant) else {
+            continue;
+        };
+        let Some(template) = item_registry.get(&event.item_id) else {
+            continue;
+        };
+        if !stock.items.iter().any(|entry| entry.item_id == event.item_id) {
+            message_log.add(format!("{} has no more {} to sell.", merchant_name.0, template.name()), Color::GRAY);
+            continue;
+        }
+        let price = buy_price(template, merchant_reputation(&npc_registry, npc, reputation));
+        if purse.gold < price {
+            message_log.add(format!("You can't afford {} ({} gold).", template.name(), price), Color::GRAY);
+            continue;
+        }
+
+        if !stow_item(&mut commands, &mut inventory, &mut item_query, &item_registry, template) {
+            message_log.add("Your pack is full.".to_string(), Color::ORANGE);
+            continue;
+        }
+
+        stock.take(&event.item_id);
+        purse.gold -= price;
+        message_log.add(format!("You buy {} for {} gold.", template.name(), price), Color::GOLD);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn sell_item_system(
+    mut commands: Commands,
+    mut sell_events: EventReader<SellItemEvent>,
+    mut player_query: Query<(&mut Inventory, &mut Purse, Option<&Reputation>), With<Player>>,
+    mut merchant_query: Query<(&Npc, &Name, &mut ShopStock), Without<Monster>>,
+    mut item_query: Query<(&mut Item, &Name, Option<&ItemTemplateRef>)>,
+    item_registry: Res<ItemTemplateRegistry>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok((mut inventory, mut purse, reputation)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    for event in sell_events.read() {
+        let Ok((npc, merchant_name, mut stock)) = merchant_query.get_mut(event.merchant) else {
+            continue;
+        };
+        let Some(index) = inventory.items.iter().position(|&carried| carried == event.item) else {
+            continue;
+        };
+        let Ok((mut item, name, template_ref)) = item_query.get_mut(event.item) else {
+            continue;
+        };
+        let template = template_ref.and_then(|template_ref| item_registry.get(&template_ref.0));
+        let price = template.map_or(0, |template| sell_price(template, merchant_reputation(&npc_registry, npc, reputation)));
+        let (Some(template), true) = (template, price > 0) else {
+            message_log.add(format!("{} has no interest in {}.", merchant_name.0, name.0), Color::GRAY);
+            continue;
+        };
+
+        if item.stack_size > 1 {
+            item.stack_size -= 1;
+        } else {
+            inventory.items.remove(index);
+            commands.entity(event.item).despawn_recursive();
+        }
+        // The merchant sells it on to the next customer
+        stock.add(template.id(), 1);
+        purse.gold += price;
+        message_log.add(format!("You sell {} for {} gold.", name.0, price), Color::GOLD);
+    }
+}
+
+/// Pockets an item off the shelf if the merchant doesn't notice. A thief who's
+/// caught is thrown out and the merchant's faction hears about it.
+#[allow(clippy::too_many_arguments)]
+fn steal_item_system(
+    mut commands: Commands,
+    mut steal_events: EventReader<StealItemEvent>,
+    mut player_query: Query<(&mut Inventory, &Stats), With<Player>>,
+    mut merchant_query: Query<(&Npc, &Name, &mut ShopStock), Without<Monster>>,
+    mut item_query: Query<(&mut Item, Option<&ItemTemplateRef>)>,
+    item_registry: Res<ItemTemplateRegistry>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    mut rng: ResMut<GameRng>,
+    mut shop_window: ResMut<ShopWindow>,
+    mut dialog: ResMut<NpcDialog>,
+    mut game_world: ResMut<GameWorld>,
+    mut reputation_events: EventWriter<ReputationChangeEvent>,
+    mut acted_events: EventWriter<PlayerActedEvent>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    let Ok((mut inventory, stats)) = player_query.get_single_mut() else {
+        return;
+    };
+    let Some(event) = steal_events.read().last() else {
+        return;
+    };
+    // Only from a shop the merchant has opened to the player
+    if shop_window.merchant != Some(event.merchant) {
+        return;
+    }
+    let Ok((npc, merchant_name, mut stock)) = merchant_query.get_mut(event.merchant) else {
+        return;
+    };
+    let Some(template) = item_registry.get(&event.item_id) else {
+        return;
+    };
+    if !stock.items.iter().any(|entry| entry.item_id == event.item_id) {
+        return;
+    }
+
+    // Caught or not, the attempt takes a turn
+    game_world.turn_count += 1;
+    acted_events.send(PlayerActedEvent);
+    if rng.gen_range(0..100) < steal_chance(stats) {
+        if stow_item(&mut commands, &mut inventory, &mut item_query, &item_registry, template) {
+            stock.take(&event.item_id);
+            message_log.add(format!("You slip {} into your pack unseen.", template.name()), Color::LIME_GREEN);
+        } else {
+            message_log.add(format!("You have nowhere to hide {}.", template.name()), Color::ORANGE);
+        }
+        return;
+    }
+
+    message_log.add(
+        format!("{} catches your hand on the {}! \"Thief! Get out of my shop!\"", merchant_name.0, template.name()),
+        Color::ORANGE_RED,
+    );
+    if let Some(faction) = npc_registry.get(&npc.template_id).and_then(|template| template.faction.clone()) {
+        reputation_events.send(ReputationChangeEvent { faction, amount: THEFT_REPUTATION });
+    }
+    shop_window.merchant = None;
+    dialog.npc = None;
+}
+
+/// Walking away from the merchant closes their shop as well
+fn close_shop_with_dialog(
+    dialog: Res<NpcDialog>,
+    mut shop_window: ResMut<ShopWindow>,
+) {
+    if shop_window.merchant.is_some() && shop_window.merchant != dialog.npc {
+        shop_window.merchant = None;
+    }
+}
+
+fn close_shop(mut shop_window: ResMut<ShopWindow>) {
+    shop_window.merchant = None;
+}
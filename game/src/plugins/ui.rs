@@ -4,11 +4,30 @@ use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
 use std::collections::HashMap;
 
-use crate::components::{Health, Monster, Player, Position, Stats, CombatStats, Name};
+use crate::components::{DialogueFlags, EquipSlot, Equipment, Experience, Health, Inventory, Item, ItemCharges, ItemCooldowns, ItemType, Level, Mana, Monster, Npc, Player, Position, Purse, Reputation, Stats, StatusEffects, StatusKind, CombatStats, Name, Viewshed};
 use crate::game_state::GameState;
-use crate::resources::MessageLog;
-use crate::plugins::combat::CurrentCombat;
-use crate::templates::monster_templates::MonsterTemplateRef;
+use crate::resources::{GameRng, GameWorld, MessageLog, NewGameSettings};
+use crate::plugins::combat::{wielded_weapon, CurrentCombat, Defending};
+use crate::plugins::combat_rules::{expected_damage, ClassicRules, Combatant, CombatRules, CombatRuleset, StatDrivenRules};
+use crate::plugins::dialogue::{AskRumorEvent, ChooseResponseEvent, Conversation, DialogueContext};
+use crate::plugins::equipment::{slot_for, EquipItemEvent, UnequipItemEvent};
+use crate::plugins::faction::{standing_label, HOSTILE_REPUTATION};
+use crate::plugins::inventory::{DropItemEvent, InventoryWindow, UseItemEvent};
+use crate::plugins::magic::{CastSpellEvent, SpellbookWindow};
+use crate::plugins::map::{Map, TileType};
+use crate::plugins::npc::{AttackNpcEvent, NpcDialog};
+use crate::plugins::player::TravelToEvent;
+use crate::plugins::progression::experience_to_next_level;
+use crate::plugins::shop::{buy_price, merchant_reputation, sell_price, steal_chance, BuyItemEvent, OpenShopEvent, SellItemEvent, ShopStock, ShopWindow, StealItemEvent};
+use crate::plugins::quest::{AcceptQuestEvent, QuestJournal, QuestLog, TurnInQuestEvent};
+use crate::plugins::save::{LoadGameEvent, SaveGameEvent, SaveIndex, AUTOSAVE_SLOT, QUICKSAVE_SLOT};
+use crate::templates::dialogue_templates::DialogueRegistry;
+use crate::templates::faction_templates::FactionRegistry;
+use crate::templates::item_templates::{DamageType, ItemTemplate, ItemTemplateRef, ItemTemplateRegistry};
+use crate::templates::monster_templates::{MonsterTemplateRef, MonsterTemplateRegistry};
+use crate::templates::npc_templates::{NPCImportance, NPCService, NPCTemplateRegistry};
+use crate::templates::quest_templates::{QuestObjective, QuestTemplate, QuestTemplateRegistry};
+use crate::templates::spell_templates::{SpellTarget, SpellTemplateRegistry};
 
 const MAP_WIDTH: i32 = 35;
 const MAP_HEIGHT: i32 = 25;
@@ -24,6 +43,17 @@ impl Plugin for UiPlugin {
                 Update,
                 ui_system.run_if(in_state(GameState::Exploring).or_else(in_state(GameState::InCombat)))
             )
+            .add_systems(
+                Update,
+                // Side panels have to be laid out before the map takes the central space
+                monster_debug_panel_system.before(ui_system).run_if(in_state(GameState::InCombat))
+            )
+            .add_systems(
+                Update,
+                (inventory_window_system, spellbook_window_system, quest_journal_window_system)
+                    .run_if(in_state(GameState::Exploring).or_else(in_state(GameState::InCombat)))
+            )
+            .add_systems(Update, (npc_dialog_window_system, shop_window_system).run_if(in_state(GameState::Exploring)))
             .add_systems(Update, game_over_system.run_if(in_state(GameState::GameOver)))
             .add_systems(OnEnter(GameState::GameOver), capture_game_over_stats)
             .add_systems(OnExit(GameState::GameOver), cleanup_game_over);
@@ -40,12 +70,13 @@ struct GameOverStats {
 
 fn capture_game_over_stats(
     mut game_over_stats: ResMut<GameOverStats>,
-    player_query: Query<&Stats, With<Player>>,
+    player_query: Query<(&Stats, Option<&Level>), With<Player>>,
     current_combat: Res<CurrentCombat>,
     monster_query: Query<&Name, With<Monster>>,
 ) {
     // Capture player stats if available
-    if let Ok(stats) = player_query.get_single() {
+    if let Ok((stats, level)) = player_query.get_single() {
+        game_over_stats.player_level = level.map_or(1, |level| level.0);
         game_over_stats.final_stats = Some((
             stats.strength,
             stats.dexterity,
@@ -55,7 +86,7 @@ fn capture_game_over_stats(
     }
     
     // Capture killer's name if in combat
-    if let Some(monster_entity) = current_combat.monster_entity {
+    if let Some(monster_entity) = current_combat.last_attacker {
         if let Ok(monster_name) = monster_query.get(monster_entity) {
             game_over_stats.killer_name = monster_name.0.clone();
         }
@@ -66,10 +97,11 @@ fn capture_game_over_stats(
     game_over_stats.monsters_slain = 0;
 }
 
+#[allow(clippy::type_complexity)]
 fn cleanup_game_over(
     mut commands: Commands,
     mut game_over_stats: ResMut<GameOverStats>,
-    entities: Query<Entity, Or<(With<Player>, With<Monster>)>>,
+    entities: Query<Entity, Or<(With<Player>, With<Monster>, With<Npc>, With<Item>)>>,
 ) {
     // Reset game over stats
     *game_over_stats = GameOverStats::default();
@@ -110,6 +142,8 @@ fn game_over_system(
             ui.group(|ui| {
                 ui.label(egui::RichText::new("Final Statistics").size(18.0).strong());
                 ui.separator();
+
+                ui.label(format!("Level: {}", game_over_stats.player_level));
                 
                 if let Some((str, dex, int, con)) = game_over_stats.final_stats {
                     ui.horizontal(|ui| {
@@ -159,12 +193,46 @@ fn game_over_system(
     }
 }
 
-fn main_menu_system(mut contexts: EguiContexts, mut next_state: ResMut<NextState<GameState>>) {
+fn main_menu_system(
+    mut contexts: EguiContexts,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut settings: ResMut<NewGameSettings>,
+    save_index: Res<SaveIndex>,
+    mut load_events: EventWriter<LoadGameEvent>,
+    mut show_load_list: Local<bool>,
+) {
     egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
         ui.vertical_centered(|ui| {
             ui.add_space(100.0);
             ui.heading("MYTHS OF ULAN");
             ui.add_space(50.0);
+            if let Some(latest) = save_index.latest() {
+                if ui.button("Continue").clicked() {
+                    load_events.send(LoadGameEvent { slot: latest.slot.clone() });
+                }
+            }
+            if ui.add_enabled(!save_index.saves.is_empty(), egui::Button::new("Load Game")).clicked() {
+                *show_load_list = !*show_load_list;
+            }
+            if *show_load_list {
+                ui.group(|ui| {
+                    for save in save_index.saves.iter() {
+                        let label = format!(
+                            "{} - Depth {}, Turn {} (seed {})",
+                            save.slot, save.header.dungeon_level, save.header.turn_count, save.header.seed
+                        );
+                        if ui.button(label).clicked() {
+                            load_events.send(LoadGameEvent { slot: save.slot.clone() });
+                        }
+                    }
+                });
+            }
+            ui.add_space(20.0);
+            ui.label("Seed (leave blank for a random run):");
+            ui.add(egui::TextEdit::singleline(&mut settings.seed_input)
+                .hint_text("random")
+                .desired_width(200.0));
+            ui.add_space(10.0);
             if ui.button("New Game").clicked() {
                 next_state.set(GameState::NewGameSetup);
             }
@@ -175,34 +243,57 @@ fn main_menu_system(mut contexts: EguiContexts, mut next_state: ResMut<NextState
     });
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn ui_system(
     mut contexts: EguiContexts,
     mut next_state: ResMut<NextState<GameState>>,
-    player_query: Query<(&Health, &Stats, &Position, &CombatStats), With<Player>>,
-    entities_query: Query<(&Position, Option<&Player>, Option<&Monster>)>,
+    player_query: Query<(&Health, Option<&Mana>, &Stats, &Position, &CombatStats, Option<&StatusEffects>), With<Player>>,
+    player_progress_query: Query<(&Level, &Experience, Option<&Purse>), With<Player>>,
+    player_viewshed_query: Query<&Viewshed, With<Player>>,
+    entities_query: Query<(&Position, Option<&Player>, Option<&Monster>, Option<&Npc>, Option<&Item>)>,
     message_log: Res<MessageLog>,
-    game_state: Res<State<GameState>>,
-    current_combat: Res<CurrentCombat>,
-    monster_query: Query<(&Name, &Health, &Stats, &CombatStats, Option<&MonsterTemplateRef>), With<Monster>>,
+    map: Res<Map>,
+    rng: Res<GameRng>,
+    game_world: Res<GameWorld>,
+    mut save_events: EventWriter<SaveGameEvent>,
+    mut travel_events: EventWriter<TravelToEvent>,
 ) {
     // Top panel - Player stats
     egui::TopBottomPanel::top("stats_panel").show(contexts.ctx_mut(), |ui| {
         ui.horizontal(|ui| {
-            if let Ok((health, stats, position, combat)) = player_query.get_single() {
+            if let Ok((health, mana, stats, position, combat, status_effects)) = player_query.get_single() {
                 ui.label(format!("HP: {}/{}", health.current, health.max));
+                status_icons(ui, status_effects, game_world.turn_count);
+                if let Some(mana) = mana {
+                    ui.separator();
+                    ui.label(egui::RichText::new(format!("MP: {}/{}", mana.current, mana.max)).color(egui::Color32::LIGHT_BLUE));
+                }
                 ui.separator();
+                if let Ok((level, experience, purse)) = player_progress_query.get_single() {
+                    ui.label(format!("Lv {} | XP: {}/{}", level.0, experience.current, experience_to_next_level(level.0)));
+                    ui.separator();
+                    ui.label(format!("Gold: {}", purse.map_or(0, |purse| purse.gold)));
+                    ui.separator();
+                }
                 ui.label(format!("STR: {} | DEX: {} | INT: {} | CON: {}", 
                     stats.strength, stats.dexterity, stats.intelligence, stats.constitution));
                 ui.separator();
                 ui.label(format!("DMG: {} | DEF: {} | ACC: {} | EVA: {}", 
                     combat.damage, combat.defense, combat.accuracy, combat.evasion));
                 ui.separator();
+                ui.label(format!("Depth: {}", position.level + 1));
                 ui.label(format!("Pos: ({}, {})", position.x, position.y));
+                ui.separator();
+                ui.label(format!("Seed: {}", rng.seed()));
             }
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if ui.button("Quit to Menu").clicked() {
+                    save_events.send(SaveGameEvent { slot: AUTOSAVE_SLOT.to_string() });
                     next_state.set(GameState::MainMenu);
                 }
+                if ui.button("Save (F5)").clicked() {
+                    save_events.send(SaveGameEvent { slot: QUICKSAVE_SLOT.to_string() });
+                }
             });
         });
     });
@@ -232,168 +323,879 @@ fn ui_system(
             });
         });
 
-    // Side panel - Monster Debug Info (only in combat)
-    if game_state.get() == &GameState::InCombat {
-        egui::SidePanel::right("monster_debug_panel")
-            .default_width(300.0)
-            .resizable(true)
-            .show(contexts.ctx_mut(), |ui| {
-                ui.heading("Monster Debug Info");
-                ui.separator();
-                
-                if let Some(monster_entity) = current_combat.monster_entity {
-                    if let Ok((name, health, stats, combat, template_ref)) = monster_query.get(monster_entity) {
-                        // Basic info
-                        ui.group(|ui| {
-                            ui.label(egui::RichText::new(&name.0).size(16.0).strong());
-                            if let Some(template_ref) = template_ref {
-                                ui.label(format!("Template: {}", template_ref.0));
+    // Central panel - Map view
+    egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
+        if let Ok((_, _, _, player_pos, _, status_effects)) = player_query.get_single() {
+            let viewshed = player_viewshed_query.get_single().ok();
+            let is_visible = |x: i32, y: i32| viewshed.is_some_and(|v| v.visible_tiles.contains(&(x, y)));
+            // Detect Monsters shows monsters nearby even through walls
+            let detection_radius = status_effects.and_then(|effects| effects.detection_radius());
+            let is_detected = |x: i32, y: i32| detection_radius
+                .is_some_and(|radius| (x - player_pos.x).abs().max((y - player_pos.y).abs()) <= radius);
+
+            // Each tile shows its most important occupant: player, then monsters and NPCs, then items
+            let mut map_chars: HashMap<(i32, i32), (u8, char, egui::Color32)> = HashMap::new();
+            for (pos, is_player, is_monster, is_npc, item) in entities_query.iter() {
+                if pos.level != player_pos.level {
+                    continue;
+                }
+                // Anything outside the player's field of view stays hidden, unless it's a sensed monster
+                let sensed = is_monster.is_some() && !is_visible(pos.x, pos.y) && is_detected(pos.x, pos.y);
+                if is_player.is_none() && !is_visible(pos.x, pos.y) && !sensed {
+                    continue;
+                }
+                let (priority, glyph, color) = if is_player.is_some() { (3, '@', egui::Color32::WHITE) }
+                    else if sensed { (2, 'g', egui::Color32::from_rgb(150, 90, 200)) }
+                    // A provoked NPC keeps its look but turns red
+                    else if is_npc.is_some() && is_monster.is_some() { (2, '@', egui::Color32::from_rgb(220, 80, 80)) }
+                    else if is_monster.is_some() { (2, 'g', egui::Color32::from_rgb(220, 80, 80)) }
+                    else if is_npc.is_some() { (2, '@', egui::Color32::from_rgb(240, 200, 90)) }
+                    else if let Some(item) = item { (1, item_glyph(&item.item_type), item_color(&item.item_type)) }
+                    else { (0, '?', egui::Color32::LIGHT_GRAY) };
+                let shown = map_chars.entry((pos.x, pos.y)).or_insert((priority, glyph, color));
+                if priority > shown.0 {
+                    *shown = (priority, glyph, color);
+                }
+            }
+            let level_map = map.get_level(player_pos.level);
+            ui.vertical_centered(|ui| {
+                let font = egui::FontId::monospace(14.0);
+                for y in 0..MAP_HEIGHT {
+                    let mut row = egui::text::LayoutJob::default();
+                    for x in 0..MAP_WIDTH {
+                        let world_x = player_pos.x + x - MAP_WIDTH / 2;
+                        let world_y = player_pos.y - (y - MAP_HEIGHT / 2);
+                        let (glyph, color) = match (map_chars.get(&(world_x, world_y)), level_map) {
+                            (Some(&(_, glyph, color)), _) => (glyph, color),
+                            (None, Some(level)) if is_visible(world_x, world_y) => {
+                                let tile = level.get_tile(world_x, world_y);
+                                (tile.glyph(), tile_color(tile))
                             }
-                            ui.label(format!("Entity ID: {:?}", monster_entity));
-                        });
-                        
-                        ui.add_space(10.0);
-                        
-                        // Health
-                        ui.group(|ui| {
-                            ui.label(egui::RichText::new("Health").strong());
-                            ui.horizontal(|ui| {
-                                ui.label("Current:");
-                                ui.label(egui::RichText::new(health.current.to_string()).color(egui::Color32::RED));
-                                ui.label("/");
-                                ui.label(egui::RichText::new(health.max.to_string()).color(egui::Color32::GREEN));
-                            });
-                            
-                            // Health bar
-                            let health_ratio = health.current as f32 / health.max as f32;
-                            ui.add(egui::ProgressBar::new(health_ratio)
-                                .text(format!("{:.0}%", health_ratio * 100.0))
-                                .fill(egui::Color32::from_rgb(200, 50, 50)));
-                        });
-                        
-                        ui.add_space(10.0);
-                        
-                        // Base Stats
-                        ui.group(|ui| {
-                            ui.label(egui::RichText::new("Base Stats").strong());
-                            egui::Grid::new("monster_stats_grid")
-                                .num_columns(2)
-                                .spacing([20.0, 4.0])
-                                .show(ui, |ui| {
-                                    ui.label("Strength:");
-                                    ui.label(stats.strength.to_string());
-                                    ui.end_row();
-                                    
-                                    ui.label("Dexterity:");
-                                    ui.label(stats.dexterity.to_string());
-                                    ui.end_row();
-                                    
-                                    ui.label("Intelligence:");
-                                    ui.label(stats.intelligence.to_string());
-                                    ui.end_row();
-                                    
-                                    ui.label("Constitution:");
-                                    ui.label(stats.constitution.to_string());
-                                    ui.end_row();
-                                });
+                            // Remembered tiles are drawn dimmed
+                            (None, Some(level)) if level.is_revealed(world_x, world_y) => {
+                                (level.get_tile(world_x, world_y).glyph(), egui::Color32::from_gray(70))
+                            }
+                            _ => (' ', egui::Color32::BLACK),
+                        };
+                        row.append(
+                            &format!("{} ", glyph),
+                            0.0,
+                            egui::TextFormat::simple(font.clone(), color),
+                        );
+                    }
+                    // Clicking a tile walks the player there
+                    let response = ui.add(egui::Label::new(row).sense(egui::Sense::click()));
+                    if let Some(pointer) = response.interact_pointer_pos().filter(|_| response.clicked()) {
+                        let cell_width = response.rect.width() / MAP_WIDTH as f32;
+                        let x = ((pointer.x - response.rect.left()) / cell_width) as i32;
+                        travel_events.send(TravelToEvent {
+                            x: player_pos.x + x - MAP_WIDTH / 2,
+                            y: player_pos.y - (y - MAP_HEIGHT / 2),
                         });
-                        
-                        ui.add_space(10.0);
-                        
-                        // Combat Stats
-                        ui.group(|ui| {
-                            ui.label(egui::RichText::new("Combat Stats").strong());
-                            egui::Grid::new("monster_combat_grid")
-                                .num_columns(2)
-                                .spacing([20.0, 4.0])
-                                .show(ui, |ui| {
-                                    ui.label("Damage:");
-                                    ui.label(egui::RichText::new(combat.damage.to_string()).color(egui::Color32::LIGHT_RED));
-                                    ui.end_row();
-                                    
-                                    ui.label("Defense:");
-                                    ui.label(egui::RichText::new(combat.defense.to_string()).color(egui::Color32::LIGHT_BLUE));
-                                    ui.end_row();
-                                    
-                                    ui.label("Accuracy:");
-                                    ui.label(format!("{}%", combat.accuracy));
-                                    ui.end_row();
-                                    
-                                    ui.label("Evasion:");
-                                    ui.label(format!("{}%", combat.evasion));
-                                    ui.end_row();
-                                });
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Side panel with the current opponent's numbers, shown while in combat
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn monster_debug_panel_system(
+    mut contexts: EguiContexts,
+    current_combat: Res<CurrentCombat>,
+    monster_query: Query<(&Name, &Health, &Stats, &CombatStats, Option<&MonsterTemplateRef>, Option<&StatusEffects>), With<Monster>>,
+    player_query: Query<(Entity, &Health, &Stats, &CombatStats, Option<&Equipment>), With<Player>>,
+    defending_query: Query<(), With<Defending>>,
+    game_world: Res<GameWorld>,
+    weapon_query: Query<(&Item, &Name, &ItemTemplateRef)>,
+    monster_registry: Res<MonsterTemplateRegistry>,
+    item_registry: Res<ItemTemplateRegistry>,
+    mut rules: ResMut<CombatRuleset>,
+) {
+    egui::SidePanel::right("monster_debug_panel")
+        .default_width(300.0)
+        .resizable(true)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.heading("Monster Debug Info");
+            ui.horizontal(|ui| {
+                ui.label("Rules:");
+                let current = rules.name();
+                if ui.selectable_label(current == StatDrivenRules.name(), StatDrivenRules.name()).clicked() {
+                    rules.0 = Box::new(StatDrivenRules);
+                }
+                if ui.selectable_label(current == ClassicRules.name(), ClassicRules.name()).clicked() {
+                    rules.0 = Box::new(ClassicRules);
+                }
+            });
+            ui.separator();
+
+            // Everyone in the fight in the order they act; hostiles are numbered by their target key
+            ui.group(|ui| {
+                ui.label(egui::RichText::new("Turn Order").strong());
+                let mut hostile_number = 0;
+                for (index, &entity) in current_combat.participants.iter().enumerate() {
+                    let acting = if index == current_combat.turn { "▶ " } else { "   " };
+                    let line = match monster_query.get(entity) {
+                        Ok((name, health, ..)) => {
+                            hostile_number += 1;
+                            format!("{}{}. {} ({}/{})", acting, hostile_number, name.0, health.current, health.max)
+                        }
+                        Err(_) => format!("{}You", acting),
+                    };
+                    let line = if defending_query.contains(entity) {
+                        format!("{} [defending]", line)
+                    } else {
+                        line
+                    };
+                    let mut text = egui::RichText::new(line);
+                    if current_combat.target == Some(entity) {
+                        text = text.color(egui::Color32::YELLOW).strong();
+                    }
+                    ui.label(text);
+                }
+            });
+
+            ui.add_space(10.0);
+            
+            if let Some(monster_entity) = current_combat.target {
+                if let Ok((name, health, stats, combat, template_ref, status_effects)) = monster_query.get(monster_entity) {
+                    // Basic info
+                    ui.group(|ui| {
+                        ui.label(egui::RichText::new(&name.0).size(16.0).strong());
+                        if let Some(template_ref) = template_ref {
+                            ui.label(format!("Template: {}", template_ref.0));
+                        }
+                        ui.label(format!("Entity ID: {:?}", monster_entity));
+                    });
+                    
+                    ui.add_space(10.0);
+                    
+                    // Health
+                    ui.group(|ui| {
+                        ui.label(egui::RichText::new("Health").strong());
+                        ui.horizontal(|ui| {
+                            ui.label("Current:");
+                            ui.label(egui::RichText::new(health.current.to_string()).color(egui::Color32::RED));
+                            ui.label("/");
+                            ui.label(egui::RichText::new(health.max.to_string()).color(egui::Color32::GREEN));
+                            status_icons(ui, status_effects, game_world.turn_count);
                         });
                         
-                        ui.add_space(10.0);
-                        
-                        // Combat calculations helper
-                        ui.group(|ui| {
-                            ui.label(egui::RichText::new("Combat Calculations vs Player").strong());
-                            if let Ok((player_health, _player_stats, _, player_combat)) = player_query.get_single() {
-                                ui.separator();
+                        // Health bar
+                        let health_ratio = health.current as f32 / health.max as f32;
+                        ui.add(egui::ProgressBar::new(health_ratio)
+                            .text(format!("{:.0}%", health_ratio * 100.0))
+                            .fill(egui::Color32::from_rgb(200, 50, 50)));
+                    });
+                    
+                    ui.add_space(10.0);
+                    
+                    // Base Stats
+                    ui.group(|ui| {
+                        ui.label(egui::RichText::new("Base Stats").strong());
+                        egui::Grid::new("monster_stats_grid")
+                            .num_columns(2)
+                            .spacing([20.0, 4.0])
+                            .show(ui, |ui| {
+                                ui.label("Strength:");
+                                ui.label(stats.strength.to_string());
+                                ui.end_row();
                                 
-                                // Monster attacking player
-                                ui.label(egui::RichText::new("Monster → Player:").underline());
-                                let m_hit_chance = combat.accuracy - player_combat.evasion;
-                                let m_damage = (combat.damage - player_combat.defense).max(1);
-                                ui.label(format!("Hit Chance: {}% - {}% = {}%", 
-                                    combat.accuracy, player_combat.evasion, m_hit_chance));
-                                ui.label(format!("Damage: {} - {} = {}", 
-                                    combat.damage, player_combat.defense, m_damage));
-                                ui.label(format!("Turns to kill: ~{}", 
-                                    (player_health.current as f32 / m_damage as f32).ceil() as i32));
+                                ui.label("Dexterity:");
+                                ui.label(stats.dexterity.to_string());
+                                ui.end_row();
                                 
-                                ui.separator();
+                                ui.label("Intelligence:");
+                                ui.label(stats.intelligence.to_string());
+                                ui.end_row();
                                 
-                                // Player attacking monster
-                                ui.label(egui::RichText::new("Player → Monster:").underline());
-                                let p_hit_chance = player_combat.accuracy - combat.evasion;
-                                let p_damage = (player_combat.damage - combat.defense).max(1);
-                                ui.label(format!("Hit Chance: {}% - {}% = {}%", 
-                                    player_combat.accuracy, combat.evasion, p_hit_chance));
-                                ui.label(format!("Damage: {} - {} = {}", 
-                                    player_combat.damage, combat.defense, p_damage));
-                                ui.label(format!("Turns to kill: ~{}", 
-                                    (health.current as f32 / p_damage as f32).ceil() as i32));
+                                ui.label("Constitution:");
+                                ui.label(stats.constitution.to_string());
+                                ui.end_row();
+                            });
+                    });
+                    
+                    ui.add_space(10.0);
+                    
+                    // Combat Stats
+                    ui.group(|ui| {
+                        ui.label(egui::RichText::new("Combat Stats").strong());
+                        egui::Grid::new("monster_combat_grid")
+                            .num_columns(2)
+                            .spacing([20.0, 4.0])
+                            .show(ui, |ui| {
+                                ui.label("Damage:");
+                                ui.label(egui::RichText::new(combat.damage.to_string()).color(egui::Color32::LIGHT_RED));
+                                ui.end_row();
+                                
+                                ui.label("Defense:");
+                                ui.label(egui::RichText::new(combat.defense.to_string()).color(egui::Color32::LIGHT_BLUE));
+                                ui.end_row();
+                                
+                                ui.label("Accuracy:");
+                                ui.label(format!("{}%", combat.accuracy));
+                                ui.end_row();
+                                
+                                ui.label("Evasion:");
+                                ui.label(format!("{}%", combat.evasion));
+                                ui.end_row();
+                            });
+                    });
+                    
+                    ui.add_space(10.0);
+
+                    // Resistances against whatever the player is wielding
+                    let player = player_query.get_single().ok();
+                    let weapon = player.and_then(|(_, _, _, _, equipment)| wielded_weapon(equipment, &weapon_query, &item_registry));
+                    let damage_type = weapon.as_ref().map_or(DamageType::Physical, |weapon| weapon.range.damage_type);
+                    let template = template_ref.and_then(|template_ref| monster_registry.get(&template_ref.0));
+                    let multiplier = template.map_or(1.0, |template| template.damage_multiplier(damage_type));
+                    ui.group(|ui| {
+                        ui.label(egui::RichText::new("Resistances").strong());
+                        let weapon_name = weapon.as_ref().map_or("bare hands", |weapon| weapon.name.as_str());
+                        let color = if multiplier > 1.0 {
+                            egui::Color32::LIGHT_GREEN
+                        } else if multiplier < 1.0 {
+                            egui::Color32::LIGHT_RED
+                        } else {
+                            egui::Color32::WHITE
+                        };
+                        ui.label(egui::RichText::new(format!("vs {} ({}): x{:.2}", weapon_name, damage_type.name(), multiplier)).color(color));
+                        if let Some(template) = template {
+                            let notable: Vec<String> = DamageType::ALL.iter()
+                                .map(|&damage_type| (damage_type, template.damage_multiplier(damage_type)))
+                                .filter(|(_, multiplier)| *multiplier != 1.0)
+                                .map(|(damage_type, multiplier)| format!("{} x{:.2}", damage_type.name(), multiplier))
+                                .collect();
+                            if !notable.is_empty() {
+                                ui.label(egui::RichText::new(notable.join(", ")).small().color(egui::Color32::GRAY));
+                            }
+                        }
+                    });
+
+                    ui.add_space(10.0);
+                    
+                    // Combat calculations helper
+                    ui.group(|ui| {
+                        ui.label(egui::RichText::new("Combat Calculations vs Player").strong());
+                        if let Some((player_entity, player_health, player_stats, player_combat, _)) = player {
+                            let monster = Combatant {
+                                stats,
+                                combat,
+                                weapon: None,
+                                defending: defending_query.contains(monster_entity),
+                            };
+                            let player = Combatant {
+                                stats: player_stats,
+                                combat: player_combat,
+                                weapon: weapon.as_ref(),
+                                defending: defending_query.contains(player_entity),
+                            };
+
+                            ui.separator();
+                            ui.label(egui::RichText::new("Monster → Player:").underline());
+                            attack_preview(ui, &**rules, &monster, &player, 1.0, player_health.current);
+
+                            ui.separator();
+                            ui.label(egui::RichText::new("Player → Monster:").underline());
+                            attack_preview(ui, &**rules, &player, &monster, multiplier, health.current);
+                        }
+                    });
+                } else {
+                    ui.label("Monster data not found!");
+                }
+            } else {
+                ui.label("No monster in combat");
+            }
+        });
+}
+
+/// One side's odds against the other, worked out by the same rules combat uses
+fn attack_preview(
+    ui: &mut egui::Ui,
+    rules: &dyn CombatRules,
+    attacker: &Combatant,
+    defender: &Combatant,
+    multiplier: f32,
+    defender_health: i32,
+) {
+    let damage = expected_damage(rules, attacker, defender, multiplier);
+    ui.label(format!("Hit Chance: {}%", rules.hit_chance(attacker, defender)));
+    ui.label(format!("Crit Chance: {}% (x{:.2})", rules.crit_chance(attacker, defender), rules.crit_multiplier(attacker)));
+    ui.label(format!("Damage: {} vs {} defense = {}", attacker.combat.damage, defender.defense(), damage));
+    if damage > 0 {
+        ui.label(format!("Turns to kill: ~{}", (defender_health as f32 / damage as f32).ceil() as i32));
+    } else {
+        ui.label("Turns to kill: never");
+    }
+}
+
+/// The player's pack, toggled with I. In a fight items can only be used.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn inventory_window_system(
+    mut contexts: EguiContexts,
+    mut inventory_window: ResMut<InventoryWindow>,
+    player_query: Query<(&Inventory, &Equipment, Option<&Purse>, Option<&ItemCooldowns>), With<Player>>,
+    item_query: Query<(&Item, &Name, Option<&ItemTemplateRef>, Option<&ItemCharges>)>,
+    registry: Res<ItemTemplateRegistry>,
+    game_world: Res<GameWorld>,
+    state: Res<State<GameState>>,
+    mut use_events: EventWriter<UseItemEvent>,
+    mut drop_events: EventWriter<DropItemEvent>,
+    mut equip_events: EventWriter<EquipItemEvent>,
+    mut unequip_events: EventWriter<UnequipItemEvent>,
+) {
+    let Ok((inventory, equipment, purse, cooldowns)) = player_query.get_single() else {
+        return;
+    };
+    let in_combat = *state.get() == GameState::InCombat;
+
+    egui::Window::new("Inventory")
+        .open(&mut inventory_window.open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("Slots: {}/{}", inventory.items.len(), inventory.capacity));
+                ui.separator();
+                ui.label(egui::RichText::new(format!("Gold: {}", purse.map_or(0, |purse| purse.gold))).color(egui::Color32::GOLD));
+            });
+            ui.separator();
+
+            for slot in [EquipSlot::Weapon, EquipSlot::Armor, EquipSlot::Shield] {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{:?}:", slot));
+                    let equipped = equipment.slot(slot).and_then(|entity| item_query.get(entity).ok());
+                    let Some((item, name, ..)) = equipped else {
+                        ui.label(egui::RichText::new("nothing").italics().color(egui::Color32::GRAY));
+                        return;
+                    };
+                    ui.label(egui::RichText::new(&name.0).color(item_color(&item.item_type)).strong());
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if !in_combat && ui.button("Unequip").clicked() {
+                            unequip_events.send(UnequipItemEvent { slot });
+                        }
+                    });
+                });
+            }
+            ui.separator();
+
+            if inventory.items.is_empty() {
+                ui.label(egui::RichText::new("Your pack is empty.").italics());
+                return;
+            }
+
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for &entity in inventory.items.iter() {
+                    let Ok((item, name, template_ref, charges)) = item_query.get(entity) else {
+                        continue;
+                    };
+                    let template = template_ref.and_then(|template_ref| registry.get(&template_ref.0));
+                    let description = template.map(|template| template.description());
+                    let equippable = slot_for(item, template).is_some();
+                    let consumable = match template {
+                        Some(ItemTemplate::Consumable(consumable)) => Some(consumable),
+                        _ => None,
+                    };
+                    let cooldown_left = consumable
+                        .and_then(|consumable| cooldowns?.0.get(&consumable.id))
+                        .map(|&ready_at| ready_at.saturating_sub(game_world.turn_count))
+                        .filter(|&left| left > 0);
+
+                    ui.horizontal(|ui| {
+                        let title = if item.stack_size > 1 {
+                            format!("{} {} x{}", item_glyph(&item.item_type), name.0, item.stack_size)
+                        } else {
+                            format!("{} {}", item_glyph(&item.item_type), name.0)
+                        };
+                        ui.label(egui::RichText::new(title).color(item_color(&item.item_type)).strong());
+                        if let Some(consumable) = consumable.filter(|consumable| consumable.charges > 1) {
+                            let left = charges.map_or(consumable.charges, |charges| charges.0);
+                            ui.label(format!("({}/{} charges)", left, consumable.charges));
+                        }
+                        if let Some(left) = cooldown_left {
+                            ui.label(egui::RichText::new(format!("ready in {} turns", left)).color(egui::Color32::GRAY));
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if !in_combat && ui.button("Drop").clicked() {
+                                drop_events.send(DropItemEvent { item: entity });
+                            }
+                            if equippable {
+                                if !in_combat && ui.button("Equip").clicked() {
+                                    equip_events.send(EquipItemEvent { item: entity });
+                                }
+                            } else if ui.add_enabled(cooldown_left.is_none(), egui::Button::new("Use")).clicked() {
+                                use_events.send(UseItemEvent { item: entity });
                             }
                         });
-                    } else {
-                        ui.label("Monster data not found!");
+                    });
+                    if let Some(description) = description {
+                        ui.label(egui::RichText::new(description).small().color(egui::Color32::GRAY));
                     }
-                } else {
-                    ui.label("No monster in combat");
+                    ui.separator();
                 }
             });
+        });
+}
+
+/// The spells the player knows, toggled with C
+fn spellbook_window_system(
+    mut contexts: EguiContexts,
+    mut spellbook_window: ResMut<SpellbookWindow>,
+    player_query: Query<(&Level, Option<&Mana>), With<Player>>,
+    spells: Res<SpellTemplateRegistry>,
+    state: Res<State<GameState>>,
+    mut cast_events: EventWriter<CastSpellEvent>,
+) {
+    let Ok((level, mana)) = player_query.get_single() else {
+        return;
+    };
+    let in_combat = *state.get() == GameState::InCombat;
+    let available_mana = mana.map_or(0, |mana| mana.current);
+
+    egui::Window::new("Spellbook")
+        .open(&mut spellbook_window.open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            if let Some(mana) = mana {
+                ui.label(egui::RichText::new(format!("Mana: {}/{}", mana.current, mana.max)).color(egui::Color32::LIGHT_BLUE));
+                ui.separator();
+            }
+
+            let known = spells.known_at(level.0);
+            if known.is_empty() {
+                ui.label(egui::RichText::new("You don't know any spells yet.").italics());
+                return;
+            }
+
+            for spell in known {
+                // Offensive spells need a fight to aim at
+                let castable = available_mana >= spell.mana_cost && (in_combat || spell.target != SpellTarget::Enemy);
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(&spell.name).color(egui::Color32::from_rgb(190, 150, 255)).strong());
+                    ui.label(format!("{} MP", spell.mana_cost));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.add_enabled(castable, egui::Button::new("Cast")).clicked() {
+                            cast_events.send(CastSpellEvent { spell_id: spell.id.clone() });
+                        }
+                    });
+                });
+                ui.label(egui::RichText::new(&spell.description).small().color(egui::Color32::GRAY));
+                ui.separator();
+            }
+        });
+}
+
+/// The quests the player has taken on, with how far along each objective is,
+/// and where they stand with the factions they've dealt with. Toggled with J.
+fn quest_journal_window_system(
+    mut contexts: EguiContexts,
+    mut journal: ResMut<QuestJournal>,
+    player_query: Query<(&QuestLog, Option<&Reputation>), With<Player>>,
+    quests: Res<QuestTemplateRegistry>,
+    items: Res<ItemTemplateRegistry>,
+    npcs: Res<NPCTemplateRegistry>,
+    factions: Res<FactionRegistry>,
+) {
+    let Ok((quest_log, reputation)) = player_query.get_single() else {
+        return;
+    };
+
+    egui::Window::new("Quest Journal")
+        .open(&mut journal.open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            if quest_log.active.is_empty() {
+                ui.label(egui::RichText::new("You haven't taken on any quests.").italics());
+            }
+            for active in &quest_log.active {
+                let Some(quest) = quests.get(&active.quest_id) else {
+                    continue;
+                };
+                let heading = if active.is_done(quest) { format!("{} (hand in)", quest.name) } else { quest.name.clone() };
+                ui.label(egui::RichText::new(heading).color(egui::Color32::GOLD).strong());
+                ui.label(egui::RichText::new(&quest.description).small().color(egui::Color32::GRAY));
+                for (index, objective) in quest.objectives.iter().enumerate() {
+                    let progress = active.progress.get(index).copied().unwrap_or(0);
+                    let color = if active.objective_done(quest, index) { egui::Color32::GRAY } else { egui::Color32::WHITE };
+                    ui.label(egui::RichText::new(format!("• {}", objective_label(objective, progress, &items, &npcs))).color(color));
+                }
+                ui.label(egui::RichText::new(reward_label(quest, &factions)).small().color(egui::Color32::GOLD));
+                ui.separator();
+            }
+
+            if !quest_log.completed.is_empty() {
+                ui.collapsing(format!("Completed ({})", quest_log.completed.len()), |ui| {
+                    for quest in quest_log.completed.iter().filter_map(|quest_id| quests.get(quest_id)) {
+                        ui.label(egui::RichText::new(format!("✔ {}", quest.name)).color(egui::Color32::GRAY));
+                    }
+                });
+            }
+
+            // Only factions the player has had dealings with
+            let standings: Vec<_> = factions.all().into_iter()
+                .filter_map(|faction| Some((faction, *reputation?.0.get(&faction.id)?)))
+                .collect();
+            if !standings.is_empty() {
+                ui.collapsing("Reputation", |ui| {
+                    for (faction, standing) in standings {
+                        ui.label(egui::RichText::new(format!("{}: {} ({:+})", faction.name, standing_label(standing), standing))
+                            .color(standing_color(standing)))
+                            .on_hover_text(&faction.description);
+                    }
+                });
+            }
+        });
+}
+
+fn standing_color(reputation: i32) -> egui::Color32 {
+    if reputation <= HOSTILE_REPUTATION {
+        egui::Color32::LIGHT_RED
+    } else if reputation < 0 {
+        egui::Color32::from_rgb(255, 165, 0)
+    } else if reputation > 0 {
+        egui::Color32::LIGHT_GREEN
+    } else {
+        egui::Color32::GRAY
     }
+}
 
-    // Central panel - Map view
-    egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
-        if let Ok((_, _, player_pos, _)) = player_query.get_single() {
-            let mut map_chars: HashMap<(i32, i32), char> = HashMap::new();
-            for (pos, is_player, is_monster) in entities_query.iter() {
-                if pos.level == player_pos.level {
-                    map_chars.insert(
-                        (pos.x, pos.y),
-                        if is_player.is_some() { '@' } 
-                        else if is_monster.is_some() { 'g' } 
-                        else { '?' }
-                    );
+fn objective_label(objective: &QuestObjective, progress: u32, items: &ItemTemplateRegistry, npcs: &NPCTemplateRegistry) -> String {
+    match objective {
+        QuestObjective::Kill { monster_type, count } => format!("Slay {} {}: {}/{}", count, monster_type, progress, count),
+        QuestObjective::Fetch { item_id, count } => {
+            let name = items.get(item_id).map_or(item_id.as_str(), |item| item.name());
+            format!("Bring back {} {}: {}/{}", count, name, progress, count)
+        }
+        // Depths are shown counting from 1, as they are everywhere else on screen
+        QuestObjective::ReachDepth { depth } => {
+            format!("Reach depth {}{}", depth + 1, if progress > 0 { ": done" } else { "" })
+        }
+        QuestObjective::TalkTo { npc } => {
+            let name = npcs.get(npc).map_or(npc.as_str(), |template| template.name.as_str());
+            format!("Speak to {}{}", name, if progress > 0 { ": done" } else { "" })
+        }
+    }
+}
+
+fn reward_label(quest: &QuestTemplate, factions: &FactionRegistry) -> String {
+    let rewards = &quest.rewards;
+    let mut parts = Vec::new();
+    if rewards.experience > 0 {
+        parts.push(format!("{} XP", rewards.experience));
+    }
+    if rewards.gold > 0 {
+        parts.push(format!("{} gold", rewards.gold));
+    }
+    if !rewards.loot_tables.is_empty() {
+        parts.push("a reward".to_string());
+    }
+    for (faction, amount) in &rewards.reputation {
+        parts.push(format!("{:+} standing with the {}", amount, factions.name(faction)));
+    }
+    if parts.is_empty() {
+        "Reward: their thanks".to_string()
+    } else {
+        format!("Reward: {}", parts.join(", "))
+    }
+}
+
+/// What the NPC the player bumped into has to say and to offer
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn npc_dialog_window_system(
+    mut contexts: EguiContexts,
+    mut dialog: ResMut<NpcDialog>,
+    conversation: Res<Conversation>,
+    npc_query: Query<(&Npc, &Name)>,
+    player_query: Query<(&Level, &Purse, &Inventory, &Reputation, &DialogueFlags, &QuestLog), With<Player>>,
+    item_query: Query<&ItemTemplateRef>,
+    registry: Res<NPCTemplateRegistry>,
+    dialogues: Res<DialogueRegistry>,
+    quests: Res<QuestTemplateRegistry>,
+    factions: Res<FactionRegistry>,
+    mut attack_events: EventWriter<AttackNpcEvent>,
+    mut shop_events: EventWriter<OpenShopEvent>,
+    mut choose_events: EventWriter<ChooseResponseEvent>,
+    mut rumor_events: EventWriter<AskRumorEvent>,
+    mut accept_events: EventWriter<AcceptQuestEvent>,
+    mut turn_in_events: EventWriter<TurnInQuestEvent>,
+) {
+    let Some(entity) = dialog.npc else {
+        return;
+    };
+    let Some((name, template)) = npc_query.get(entity).ok()
+        .and_then(|(npc, name)| Some((name, registry.get(&npc.template_id)?)))
+    else {
+        return;
+    };
+    let node = conversation.node.as_deref()
+        .and_then(|node| dialogues.get(&template.id)?.node(node));
+    let quest_log = player_query.get_single().ok().map(|(.., quest_log)| quest_log);
+    let context = player_query.get_single().ok().map(|(level, purse, inventory, reputation, flags, _)| DialogueContext {
+        level: level.0,
+        gold: purse.gold,
+        carried: inventory.items.iter()
+            .filter_map(|&carried| item_query.get(carried).ok())
+            .map(|template_ref| template_ref.0.clone())
+            .collect(),
+        reputation,
+        flags,
+    });
+
+    let mut open = true;
+    egui::Window::new(&name.0)
+        .id(egui::Id::new("npc_dialog"))
+        .open(&mut open)
+        .resizable(false)
+        .collapsible(false)
+        .show(contexts.ctx_mut(), |ui| {
+            if let Some(title) = &template.title {
+                ui.label(egui::RichText::new(title).italics().color(egui::Color32::GOLD));
+            }
+            ui.label(&template.description);
+            if let Some(faction) = &template.faction {
+                let standing = context.as_ref().map_or(0, |context| context.reputation.with(faction));
+                ui.label(egui::RichText::new(format!("{} · {}", factions.name(faction), standing_label(standing)))
+                    .small()
+                    .color(standing_color(standing)));
+            }
+            ui.separator();
+
+            if !conversation.line.is_empty() {
+                ui.label(egui::RichText::new(format!("\"{}\"", conversation.line)).color(egui::Color32::LIGHT_YELLOW));
+                ui.add_space(4.0);
+            }
+            // Replies the player doesn't qualify for aren't shown at all
+            let responses = node.iter()
+                .flat_map(|node| node.responses.iter().enumerate())
+                .filter(|(_, response)| context.as_ref().is_some_and(|context| context.allows_response(response)));
+            for (number, (index, response)) in responses.enumerate() {
+                if ui.button(format!("{}. {}", number + 1, response.text)).clicked() {
+                    choose_events.send(ChooseResponseEvent { index });
                 }
             }
-            ui.vertical_centered(|ui| {
-                let font = egui::FontId::monospace(14.0);
-                for y in 0..MAP_HEIGHT {
-                    let mut row_string = String::new();
-                    for x in 0..MAP_WIDTH {
-                        let world_x = player_pos.x + x - MAP_WIDTH / 2;
-                        let world_y = player_pos.y - (y - MAP_HEIGHT / 2);
-                        row_string.push(*map_chars.get(&(world_x, world_y)).unwrap_or(&'.'));
-                        row_string.push(' ');
+            if ui.button("Heard any rumors?").clicked() {
+                rumor_events.send(AskRumorEvent);
+            }
+            ui.separator();
+
+            if template.services.is_empty() {
+                ui.label(egui::RichText::new("They have nothing to offer you.").italics());
+            }
+            for service in &template.services {
+                match service {
+                    NPCService::Shop { .. } => {
+                        if ui.button(service.label()).clicked() {
+                            shop_events.send(OpenShopEvent { merchant: entity });
+                        }
+                    }
+                    NPCService::Quest { quest_ids } => {
+                        for quest in quest_ids.iter().filter_map(|quest_id| quests.get(quest_id)) {
+                            let Some(quest_log) = quest_log else {
+                                continue;
+                            };
+                            if quest_log.is_completed(&quest.id) {
+                                ui.label(egui::RichText::new(format!("✔ {}", quest.name)).color(egui::Color32::GRAY));
+                            } else if let Some(active) = quest_log.active(&quest.id) {
+                                if active.is_done(quest) {
+                                    if ui.button(format!("Hand in: {}", quest.name)).clicked() {
+                                        turn_in_events.send(TurnInQuestEvent { giver: entity, quest_id: quest.id.clone() });
+                                    }
+                                } else {
+                                    ui.label(egui::RichText::new(format!("{} (in progress)", quest.name)).italics());
+                                }
+                            } else if ui.button(format!("Quest: {}", quest.name)).on_hover_text(&quest.description).clicked() {
+                                accept_events.send(AcceptQuestEvent { giver: entity, quest_id: quest.id.clone() });
+                            }
+                        }
+                    }
+                    _ => {
+                        ui.label(format!("• {}", service.label()));
                     }
-                    ui.label(egui::RichText::new(row_string).font(font.clone()));
+                }
+            }
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let essential = template.importance == NPCImportance::Essential;
+                let label = if dialog.attack_warned { "Attack anyway" } else { "Attack" };
+                let attack = ui.add_enabled(!essential, egui::Button::new(label))
+                    .on_disabled_hover_text(format!("{} can't be harmed.", name.0));
+                if attack.clicked() {
+                    attack_events.send(AttackNpcEvent { npc: entity });
+                }
+                if ui.button("Goodbye").clicked() {
+                    dialog.npc = None;
                 }
             });
-        }
-    });
-}
\ No newline at end of file
+        });
+    if !open {
+        dialog.npc = None;
+    }
+}
+
+/// A merchant's wares next to the player's pack, with what each would cost or fetch
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn shop_window_system(
+    mut contexts: EguiContexts,
+    mut shop_window: ResMut<ShopWindow>,
+    player_query: Query<(&Inventory, &Stats, Option<&Purse>, Option<&Reputation>), With<Player>>,
+    merchant_query: Query<(&Npc, &Name, Option<&ShopStock>)>,
+    item_query: Query<(&Item, &Name, Option<&ItemTemplateRef>)>,
+    item_registry: Res<ItemTemplateRegistry>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    mut buy_events: EventWriter<BuyItemEvent>,
+    mut sell_events: EventWriter<SellItemEvent>,
+    mut steal_events: EventWriter<StealItemEvent>,
+) {
+    let Some(merchant) = shop_window.merchant else {
+        return;
+    };
+    let (Ok((inventory, stats, purse, reputation)), Ok((npc, merchant_name, stock))) =
+        (player_query.get_single(), merchant_query.get(merchant))
+    else {
+        return;
+    };
+    let gold = purse.map_or(0, |purse| purse.gold);
+    let standing = merchant_reputation(&npc_registry, npc, reputation);
+    let steal_odds = steal_chance(stats);
+
+    let mut open = true;
+    egui::Window::new(format!("{}'s Shop", merchant_name.0))
+        .id(egui::Id::new("shop_window"))
+        .open(&mut open)
+        .resizable(false)
+        .collapsible(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(format!("Gold: {}", gold)).color(egui::Color32::GOLD));
+                ui.separator();
+                ui.label(format!("Slots: {}/{}", inventory.items.len(), inventory.capacity));
+            });
+            ui.separator();
+
+            ui.heading("Buy");
+            let wares = stock.map_or(&[][..], |stock| stock.items.as_slice());
+            if wares.is_empty() {
+                ui.label(egui::RichText::new("The shelves are bare.").italics());
+            }
+            for entry in wares {
+                let Some(template) = item_registry.get(&entry.item_id) else {
+                    continue;
+                };
+                let price = buy_price(template, standing);
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(format!("{} x{}", template.name(), entry.quantity)).strong());
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let steal = ui.button("Steal")
+                            .on_hover_text(format!("{}% chance {} doesn't notice", steal_odds, merchant_name.0));
+                        if steal.clicked() {
+                            steal_events.send(StealItemEvent { merchant, item_id: entry.item_id.clone() });
+                        }
+                        let buy = ui.add_enabled(gold >= price, egui::Button::new("Buy"))
+                            .on_disabled_hover_text("You can't afford it.");
+                        if buy.clicked() {
+                            buy_events.send(BuyItemEvent { merchant, item_id: entry.item_id.clone() });
+                        }
+                        ui.label(egui::RichText::new(format!("{} gold", price)).color(egui::Color32::GOLD));
+                    });
+                });
+            }
+            ui.separator();
+
+            ui.heading("Sell");
+            if inventory.items.is_empty() {
+                ui.label(egui::RichText::new("You have nothing to sell.").italics());
+            }
+            for &entity in inventory.items.iter() {
+                let Ok((item, name, template_ref)) = item_query.get(entity) else {
+                    continue;
+                };
+                let price = template_ref
+                    .and_then(|template_ref| item_registry.get(&template_ref.0))
+                    .map_or(0, |template| sell_price(template, standing));
+                ui.horizontal(|ui| {
+                    let title = if item.stack_size > 1 {
+                        format!("{} x{}", name.0, item.stack_size)
+                    } else {
+                        name.0.clone()
+                    };
+                    ui.label(egui::RichText::new(title).color(item_color(&item.item_type)));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let sell = ui.add_enabled(price > 0, egui::Button::new("Sell"))
+                            .on_disabled_hover_text("Worthless to a merchant.");
+                        if sell.clicked() {
+                            sell_events.send(SellItemEvent { merchant, item: entity });
+                        }
+                        ui.label(egui::RichText::new(format!("{} gold", price)).color(egui::Color32::GOLD));
+                    });
+                });
+            }
+        });
+    if !open {
+        shop_window.merchant = None;
+    }
+}
+
+/// An icon and the turns left for every active status effect, explained on hover
+fn status_icons(ui: &mut egui::Ui, status_effects: Option<&StatusEffects>, turn: u32) {
+    for effect in status_effects.iter().flat_map(|effects| effects.0.iter()) {
+        let (color, description) = match &effect.kind {
+            StatusKind::Poison { damage } => (
+                egui::Color32::GREEN,
+                format!("Poisoned by {}: {} damage a turn", effect.source, damage * effect.stacks as i32),
+            ),
+            StatusKind::Regeneration { amount } => (
+                egui::Color32::LIGHT_GREEN,
+                format!("Regenerating {} HP a turn", amount),
+            ),
+            StatusKind::StatBuff { stat, amount } => (
+                egui::Color32::LIGHT_BLUE,
+                format!("{}: {:+} {}", effect.source, amount, stat),
+            ),
+            StatusKind::Curse { stat, amount } => (
+                egui::Color32::from_rgb(170, 80, 200),
+                format!("Cursed by {}: {} {}", effect.source, -amount, stat),
+            ),
+            StatusKind::DetectMonsters { radius } => (
+                egui::Color32::from_rgb(150, 90, 200),
+                format!("Sensing monsters within {} tiles", radius),
+            ),
+        };
+        let text = if effect.stacks > 1 {
+            format!("{}x{} {}", effect.kind.icon(), effect.stacks, effect.remaining(turn))
+        } else {
+            format!("{}{}", effect.kind.icon(), effect.remaining(turn))
+        };
+        ui.label(egui::RichText::new(text).color(color))
+            .on_hover_text(format!("{} ({} turns left)", description, effect.remaining(turn)));
+    }
+}
+
+fn item_glyph(item_type: &ItemType) -> char {
+    match item_type {
+        ItemType::Weapon { .. } => ')',
+        ItemType::Armor { .. } => '[',
+        ItemType::Potion { .. } => '!',
+        ItemType::Gold { .. } => '$',
+        ItemType::Misc => '*',
+    }
+}
+
+fn item_color(item_type: &ItemType) -> egui::Color32 {
+    match item_type {
+        ItemType::Weapon { .. } | ItemType::Armor { .. } => egui::Color32::from_rgb(120, 170, 230),
+        ItemType::Potion { .. } => egui::Color32::from_rgb(200, 100, 220),
+        ItemType::Gold { .. } => egui::Color32::GOLD,
+        ItemType::Misc => egui::Color32::LIGHT_GRAY,
+    }
+}
+
+fn tile_color(tile: TileType) -> egui::Color32 {
+    match tile {
+        TileType::Floor => egui::Color32::from_gray(150),
+        TileType::Wall => egui::Color32::from_rgb(190, 180, 160),
+        TileType::Door => egui::Color32::from_rgb(180, 120, 60),
+        TileType::DownStairs | TileType::UpStairs => egui::Color32::from_rgb(230, 210, 90),
+    }
+}
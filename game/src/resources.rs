@@ -1,21 +1,17 @@
 // src/resources.rs
 use bevy::prelude::*;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Resource)]
-pub enum TurnState {
-    #[default]
-    PlayerTurn,
-    MonsterTurn,
-}
-
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
 pub struct GameWorld {
     pub current_dungeon: Option<String>,
     pub dungeon_level: i32,
     pub turn_count: u32,
 }
 
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
 pub struct MessageLog {
     pub messages: Vec<(String, Color)>,
 }
@@ -27,4 +23,89 @@ impl MessageLog {
             self.messages.remove(0);
         }
     }
-}
\ No newline at end of file
+}
+
+/// The single source of randomness for a run. Every gameplay roll draws from
+/// this resource so that a seed plus the same input sequence replays exactly.
+/// ChaCha is used directly rather than `StdRng` because its stream is stable
+/// across rand versions and its position can be saved and restored.
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    rng: ChaCha12Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Recreates a generator part way through its stream, as recorded by `stream_position`
+    pub fn restore(seed: u64, stream_position: u128) -> Self {
+        let mut game_rng = Self::new(seed);
+        game_rng.rng.set_word_pos(stream_position);
+        game_rng
+    }
+
+    /// The seed this run was started from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// How far into the seeded stream the generator has advanced
+    pub fn stream_position(&self) -> u128 {
+        self.rng.get_word_pos()
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(rand::random())
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// Options chosen on the New Game screen
+#[derive(Resource, Default)]
+pub struct NewGameSettings {
+    /// Raw seed text typed by the player; blank means a random seed
+    pub seed_input: String,
+}
+
+impl NewGameSettings {
+    /// Numbers are used as-is, any other text is hashed so that seeds like
+    /// "ulan" are shareable too.
+    pub fn resolve_seed(&self) -> u64 {
+        let input = self.seed_input.trim();
+        if input.is_empty() {
+            return rand::random();
+        }
+        if let Ok(seed) = input.parse::<u64>() {
+            return seed;
+        }
+        // FNV-1a, so the same text maps to the same seed on every platform
+        input.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+}
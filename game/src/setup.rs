@@ -2,10 +2,18 @@
 
 use bevy::prelude::*;
 use crate::components::*;
-use crate::resources::MessageLog;
+use crate::resources::{GameRng, GameWorld, MessageLog, NewGameSettings};
 use crate::game_state::GameState;
-use crate::templates::monster_templates::{MonsterTemplateRegistry, spawn_monster_from_template};
-use rand::prelude::*;
+use crate::plugins::dungeon::generate_level;
+use crate::plugins::equipment::derive_combat_stats;
+use crate::plugins::magic::max_mana;
+use crate::plugins::inventory::INVENTORY_CAPACITY;
+use crate::plugins::map::{Map, PLAYER_VIEW_RANGE};
+use crate::plugins::quest::QuestLog;
+use crate::systems::monster_spawning::MonsterDensityConfig;
+use crate::templates::item_templates::ItemTemplateRegistry;
+use crate::templates::monster_templates::MonsterTemplateRegistry;
+use crate::templates::npc_templates::NPCTemplateRegistry;
 
 pub struct SetupPlugin;
 
@@ -14,6 +22,7 @@ impl Plugin for SetupPlugin {
         app
             .add_systems(OnEnter(GameState::NewGameSetup), (
                 cleanup_old_game,
+                seed_game_rng,
                 setup_new_game,
                 transition_to_exploring,
             ).chain());
@@ -25,10 +34,13 @@ fn transition_to_exploring(mut next_state: ResMut<NextState<GameState>>) {
     next_state.set(GameState::Exploring);
 }
 
+#[allow(clippy::type_complexity)]
 fn cleanup_old_game(
     mut commands: Commands,
-    entities: Query<Entity, Or<(With<Player>, With<Monster>)>>,
+    entities: Query<Entity, Or<(With<Player>, With<Monster>, With<Npc>, With<Item>)>>,
     mut message_log: ResMut<MessageLog>,
+    mut map: ResMut<Map>,
+    mut game_world: ResMut<GameWorld>,
 ) {
     // Clear all game entities
     for entity in entities.iter() {
@@ -37,104 +49,67 @@ fn cleanup_old_game(
     
     // Clear message log
     message_log.messages.clear();
+
+    // Forget the previous run's dungeon
+    map.clear();
+    *game_world = GameWorld::default();
 }
 
+fn seed_game_rng(
+    settings: Res<NewGameSettings>,
+    mut rng: ResMut<GameRng>,
+) {
+    *rng = GameRng::new(settings.resolve_seed());
+    info!("Starting new run with seed {}", rng.seed());
+}
+
+#[allow(clippy::too_many_arguments)]
 fn setup_new_game(
     mut commands: Commands,
     mut message_log: ResMut<MessageLog>,
     template_registry: Res<MonsterTemplateRegistry>,
+    item_registry: Res<ItemTemplateRegistry>,
+    npc_registry: Res<NPCTemplateRegistry>,
+    density: Res<MonsterDensityConfig>,
+    mut map: ResMut<Map>,
+    mut rng: ResMut<GameRng>,
 ) {
-    // Spawn the player at (0, 0)
+    // The first level is populated from the loaded templates; the player starts
+    // in the middle of its first room
+    let (start_x, start_y) = generate_level(&mut commands, &mut map, &template_registry, &item_registry, &npc_registry, &density, 0, &mut rng);
+    let stats = Stats {
+        strength: 10,
+        dexterity: 10,
+        intelligence: 10,
+        constitution: 10,
+    };
     commands.spawn((
         Player,
-        Position { x: 0, y: 0, level: 0 },
+        Position { x: start_x, y: start_y, level: 0 },
         Health { current: 30, max: 30 },
-        Stats {
-            strength: 10,
-            dexterity: 10,
-            intelligence: 10,
-            constitution: 10,
-        },
-        CombatStats {
-            damage: 5,
-            defense: 2,
-            accuracy: 75,
-            evasion: 10,
-        },
+        derive_combat_stats(&stats, [], &[]),
+        Mana { current: max_mana(&stats), max: max_mana(&stats), last_regen: 0 },
+        stats,
+        Level(1),
+        Experience::default(),
+        Luck::default(),
+        // Bundles top out at fifteen components, so the belongings go in one of their own
+        (
+            Inventory::new(INVENTORY_CAPACITY),
+            Equipment::default(),
+            ItemCooldowns::default(),
+            Purse::default(),
+            Reputation::default(),
+        ),
+        StatusEffects::default(),
+        DialogueFlags::default(),
+        QuestLog::default(),
+        Viewshed::new(PLAYER_VIEW_RANGE),
         Name("Player".to_string()),
     ));
 
-    // Spawn random goblins from the loaded templates
-    spawn_random_goblins(&mut commands, &template_registry);
-
     message_log.add(
-        "Welcome to Myths of Ulan! Move with WASD or arrow keys.".to_string(),
+        "Welcome to Myths of Ulan! Move with WASD or arrow keys, take stairs with < and >, pick things up with G, open your pack with I, your spellbook with C and your quest journal with J.".to_string(),
         Color::LIME_GREEN,
     );
 }
-
-/// Spawns random goblins in the starting area
-fn spawn_random_goblins(
-    commands: &mut Commands,
-    template_registry: &MonsterTemplateRegistry,
-) {
-    // Define spawn parameters
-    const NUM_GOBLINS: usize = 2;
-    const MIN_LEVEL: i32 = 1;
-    const MAX_LEVEL: i32 = 3;
-
-    // Possible spawn positions (avoiding the player's starting position at 0,0)
-    let possible_positions = vec![
-        Position { x: 5, y: 5, level: 0 },
-        Position { x: -3, y: 2, level: 0 },
-        Position { x: 3, y: -4, level: 0 },
-        Position { x: -5, y: -2, level: 0 },
-        Position { x: 7, y: 1, level: 0 },
-    ];
-
-    let mut rng = thread_rng();
-    let mut used_positions: Vec<Position> = Vec::new();
-
-    // Get all valid goblin templates in the desired level range *once*
-    let goblin_templates = template_registry.get_templates_by_type_and_level("Goblin", MIN_LEVEL, MAX_LEVEL);
-
-    if goblin_templates.is_empty() {
-        warn!("No 'Goblin' type templates found for level range {}-{}", MIN_LEVEL, MAX_LEVEL);
-        return;
-    }
-
-    for i in 0..NUM_GOBLINS {
-        // Choose a random template from the pre-filtered list
-        if let Some(goblin_template) = goblin_templates.choose(&mut rng) {
-            // Choose a random position that hasn't been used
-            let available_positions: Vec<&Position> = possible_positions
-                .iter()
-                .filter(|pos| !used_positions.contains(pos))
-                .collect();
-
-            if let Some(&position) = available_positions.choose(&mut rng) {
-                used_positions.push(*position);
-
-                // Generate a random level within the spawn parameters, clamped to the template's own limits
-                let level = rng.gen_range(MIN_LEVEL..=MAX_LEVEL)
-                    .clamp(goblin_template.level_range.0, goblin_template.level_range.1);
-
-                // Spawn the monster using the chosen template and position
-                if spawn_monster_from_template(
-                    commands,
-                    template_registry,
-                    &goblin_template.id,
-                    *position,
-                    Some(level),
-                ).is_some() {
-                    info!("Spawned {} (Lvl {}) at ({}, {})", goblin_template.name, level, position.x, position.y);
-                } else {
-                    // This case should be rare now
-                    warn!("Failed to spawn monster from template: {}", goblin_template.id);
-                }
-            } else {
-                warn!("Could not find an available position for goblin spawn #{}", i + 1);
-            }
-        }
-    }
-}
\ No newline at end of file
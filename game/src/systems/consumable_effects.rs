@@ -0,0 +1,143 @@
+// src/systems/consumable_effects.rs
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+use crate::components::{Health, Mana, Name, Player, Position, StatusEffects, StatusKind};
+use crate::game_state::GameState;
+use crate::plugins::combat::CurrentCombat;
+use crate::plugins::map::Map;
+use crate::resources::{GameRng, MessageLog};
+use crate::systems::status_effects::{describe, ApplyStatusEvent};
+use crate::templates::item_templates::ConsumableEffect;
+
+/// Event for applying a consumable's effects to someone. Whoever sends it has
+/// already paid for the use; this only decides what happens.
+#[derive(Event)]
+pub struct ApplyEffectsEvent {
+    pub target: Entity,
+    /// What the effects came from, e.g. "Minor Health Potion"
+    pub source: String,
+    pub effects: Vec<ConsumableEffect>,
+}
+
+pub struct ConsumableEffectsPlugin;
+
+impl Plugin for ConsumableEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<ApplyEffectsEvent>()
+            // In every state, so potions work in the middle of a fight as well
+            .add_systems(Update, apply_effects_system);
+    }
+}
+
+/// Template durations and cooldowns are written in turns
+pub fn turns(duration: f32) -> u32 {
+    duration.round().max(0.0) as u32
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn apply_effects_system(
+    mut events: EventReader<ApplyEffectsEvent>,
+    mut targets: Query<(&Name, &mut Position, &mut Health, Option<&mut Mana>, Option<&mut StatusEffects>, Has<Player>)>,
+    map: Res<Map>,
+    mut current_combat: ResMut<CurrentCombat>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut rng: ResMut<GameRng>,
+    mut message_log: ResMut<MessageLog>,
+    mut status_events: EventWriter<ApplyStatusEvent>,
+) {
+    for event in events.read() {
+        // Creatures block teleport destinations, so note where they all stand first
+        let occupied: Vec<Position> = targets.iter().map(|(_, position, ..)| *position).collect();
+
+        let Ok((name, mut position, mut health, mut mana, mut status_effects, is_player)) = targets.get_mut(event.target) else {
+            continue;
+        };
+
+        for effect in &event.effects {
+            match effect {
+                ConsumableEffect::Heal { amount } => {
+                    let healed = (*amount).min(health.max - health.current).max(0);
+                    health.current += healed;
+                    let (who, verb) = describe(&name.0, is_player, ("recover", "recovers"));
+                    message_log.add(format!("{} {} {} HP.", who, verb, healed), Color::LIME_GREEN);
+                }
+                ConsumableEffect::RestoreMana { amount } => {
+                    let Some(mana) = mana.as_mut() else {
+                        let (who, verb) = describe(&name.0, is_player, ("have", "has"));
+                        message_log.add(format!("{} {} no mana to restore.", who, verb), Color::GRAY);
+                        continue;
+                    };
+                    let restored = (*amount).min(mana.max - mana.current).max(0);
+                    mana.current += restored;
+                    let (who, verb) = describe(&name.0, is_player, ("regain", "regains"));
+                    message_log.add(format!("{} {} {} mana.", who, verb, restored), Color::CYAN);
+                }
+                ConsumableEffect::Buff { stat, amount, duration } => {
+                    status_events.send(ApplyStatusEvent {
+                        target: event.target,
+                        source: event.source.clone(),
+                        kind: StatusKind::StatBuff { stat: stat.clone(), amount: *amount },
+                        duration: turns(*duration),
+                    });
+                }
+                ConsumableEffect::Regenerate { amount, duration } => {
+                    status_events.send(ApplyStatusEvent {
+                        target: event.target,
+                        source: event.source.clone(),
+                        kind: StatusKind::Regeneration { amount: *amount },
+                        duration: turns(*duration),
+                    });
+                }
+                ConsumableEffect::CurePoison => {
+                    let cured = status_effects.as_mut()
+                        .map_or(0, |effects| effects.remove_where(|kind| matches!(kind, StatusKind::Poison { .. })));
+                    if cured > 0 {
+                        let (who, verb) = describe(&name.0, is_player, ("are", "is"));
+                        message_log.add(format!("{} {} cured of poison.", who, verb), Color::LIME_GREEN);
+                    } else {
+                        message_log.add("There is no poison to cure.".to_string(), Color::GRAY);
+                    }
+                }
+                ConsumableEffect::RemoveCurse => {
+                    let lifted = status_effects.as_mut()
+                        .map_or(0, |effects| effects.remove_where(|kind| matches!(kind, StatusKind::Curse { .. })));
+                    if lifted > 0 {
+                        let (who, verb) = describe(&name.0, is_player, ("feel", "feels"));
+                        message_log.add(format!("{} {} the curse lift.", who, verb), Color::LIME_GREEN);
+                    } else {
+                        message_log.add("There is no curse to lift.".to_string(), Color::GRAY);
+                    }
+                }
+                ConsumableEffect::Teleport => {
+                    let Some(level) = map.get_level(position.level) else {
+                        continue;
+                    };
+                    let destinations: Vec<Position> = (0..level.height)
+                        .flat_map(|y| (0..level.width).map(move |x| (x, y)))
+                        .filter(|&(x, y)| level.is_walkable(x, y))
+                        .map(|(x, y)| Position { x, y, level: position.level })
+                        .filter(|tile| !occupied.contains(tile))
+                        .collect();
+                    let Some(destination) = destinations.choose(&mut *rng) else {
+                        message_log.add("The air shimmers, but nothing happens.".to_string(), Color::GRAY);
+                        continue;
+                    };
+                    *position = *destination;
+
+                    let (who, verb) = describe(&name.0, is_player, ("vanish and reappear", "vanishes and reappears"));
+                    message_log.add(format!("{} {} elsewhere!", who, verb), Color::FUCHSIA);
+
+                    // Whoever teleports is out of the fight
+                    if current_combat.contains(event.target) {
+                        if is_player {
+                            next_state.set(GameState::Exploring);
+                        } else {
+                            current_combat.remove(event.target);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
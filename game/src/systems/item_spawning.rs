@@ -1,53 +1,83 @@
-// src/systems/item_spawning.rs  - example system for spawning items in a game
+// src/systems/item_spawning.rs
 use bevy::prelude::*;
-use crate::components::{Item, ItemType, Position};
-use crate::data::GameData;
-use crate::data::templates::{ItemTemplate, WeaponTemplate, ConsumableTemplate};
+use rand::prelude::*;
+use crate::components::{Item, ItemType, Name, Position};
+use crate::plugins::map::LevelMap;
+use crate::systems::monster_spawning::monster_level_for_depth;
+use crate::templates::item_templates::{
+    ArmorTemplate, ConsumableEffect, ConsumableTemplate, ItemTemplate, ItemTemplateRegistry, ItemTemplateRef,
+    WeaponTemplate,
+};
 
+/// Chance that any given room has an item lying in it
+const ITEM_CHANCE_PER_ROOM: f64 = 0.3;
+
+/// Spawns `quantity` of an item as a single stack, capped at the template's stack size
 pub fn spawn_item_from_template(
     commands: &mut Commands,
-    game_data: &GameData,
+    registry: &ItemTemplateRegistry,
     item_id: &str,
     position: Position,
+    quantity: u32,
 ) -> Option<Entity> {
-    let template = game_data.items.get(item_id)?;
+    let template = registry.get(item_id)?;
 
-    match template {
-        ItemTemplate::Weapon(weapon) => {
-            spawn_weapon(commands, weapon, position)
-        }
-        ItemTemplate::Consumable(consumable) => {
-            spawn_consumable(commands, consumable, position)
-        }
-        // Add more item types as needed
-        _ => None,
-    }
+    let entity = commands.spawn((
+        item_from_template(template, quantity),
+        Name(template.name().to_string()),
+        position,
+        ItemTemplateRef(template.id().to_string()),
+    )).id();
+
+    Some(entity)
 }
 
-fn spawn_weapon(
+/// Spawns a stack straight into someone's pack, so it has no place on the map
+pub fn spawn_carried_item(
     commands: &mut Commands,
-    template: &WeaponTemplate,
-    position: Position,
+    registry: &ItemTemplateRegistry,
+    item_id: &str,
+    quantity: u32,
 ) -> Option<Entity> {
+    let template = registry.get(item_id)?;
+
     let entity = commands.spawn((
-        Item {
-            item_type: ItemType::Weapon {
-                damage: (template.damage.min + template.damage.max) / 2,
-            },
-            stack_size: template.stack_size,
-        },
-        Name(template.name.clone()),
-        position,
-        // add visual components, etc...
+        item_from_template(template, quantity),
+        Name(template.name().to_string()),
+        ItemTemplateRef(template.id().to_string()),
     )).id();
+
+    Some(entity)
 }
 
-fn spawn_consumable(
-    commands: &mut Commands,
-    template: &ConsumableTemplate,
-    position: Position,
-) -> Option<Entity> {
-    // Convert template effects to actual item properties
+/// The `Item` component for a stack of `quantity` items made from a template
+fn item_from_template(template: &ItemTemplate, quantity: u32) -> Item {
+    let item_type = match template {
+        ItemTemplate::Weapon(weapon) => weapon_type(weapon),
+        ItemTemplate::Armor(armor) => armor_type(armor),
+        ItemTemplate::Consumable(consumable) => consumable_type(consumable),
+        ItemTemplate::Misc(_) => ItemType::Misc,
+    };
+
+    Item {
+        item_type,
+        stack_size: quantity.clamp(1, template.max_stack()),
+    }
+}
+
+fn weapon_type(template: &WeaponTemplate) -> ItemType {
+    ItemType::Weapon {
+        damage: (template.damage.min + template.damage.max) / 2,
+    }
+}
+
+fn armor_type(template: &ArmorTemplate) -> ItemType {
+    ItemType::Armor { defense: template.defense }
+}
+
+/// Consumables spawned from templates are used through their template's effects;
+/// the item type only records how much they heal, for anything that asks.
+fn consumable_type(template: &ConsumableTemplate) -> ItemType {
     let heal_amount = template.effects.iter()
         .find_map(|effect| {
             if let ConsumableEffect::Heal { amount } = effect {
@@ -57,15 +87,33 @@ fn spawn_consumable(
             }
         })
         .unwrap_or(0);
-    
-    let entity = commands.spawn((
-        Item {
-            item_type: ItemType::Potion { heal_amount },
-            stack_size: template.stack_size,
-        },
-        Name(template.name.clone()),
-        position,
-    )).id();
-    
-    Some(entity)
-}
\ No newline at end of file
+
+    ItemType::Potion { heal_amount }
+}
+
+/// Scatters items suited to the level's depth across its rooms. Rooms listed in
+/// `skip_rooms` (where the player arrives) are left empty.
+pub fn populate_items<R: Rng + ?Sized>(
+    commands: &mut Commands,
+    registry: &ItemTemplateRegistry,
+    level_map: &LevelMap,
+    depth: i32,
+    skip_rooms: usize,
+    rng: &mut R,
+) {
+    let candidates = registry.get_templates_up_to_level(monster_level_for_depth(depth));
+    if candidates.is_empty() {
+        return;
+    }
+
+    for room in level_map.rooms.iter().skip(skip_rooms) {
+        if !rng.gen_bool(ITEM_CHANCE_PER_ROOM) {
+            continue;
+        }
+        let tiles = level_map.floor_tiles_in(room);
+        let (Some(&(x, y)), Some(template)) = (tiles.choose(rng), candidates.choose(rng)) else {
+            continue;
+        };
+        spawn_item_from_template(commands, registry, template.id(), Position { x, y, level: depth }, 1);
+    }
+}
@@ -1,9 +1,19 @@
-// src/systems/loot_system.rs - Example of using loot tables
+// src/systems/loot_system.rs
 use bevy::prelude::*;
-use crate::data::GameData;
-use crate::data::templates::LootResult;
-use crate::components::Position;
+use crate::components::{Item, ItemType, Name, Position};
+use crate::plugins::map::Map;
+use crate::plugins::progression::GainExperienceEvent;
+use crate::resources::GameRng;
+use crate::systems::item_spawning::spawn_item_from_template;
+use crate::templates::item_templates::ItemTemplateRegistry;
+use crate::templates::loot_tables::{LootResult, LootTableRegistry};
 
+/// Tiles around a drop point that loot spills onto, starting with the point itself
+const SPILL_OFFSETS: [(i32, i32); 9] = [
+    (0, 0), (1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (-1, -1), (1, -1), (-1, 1),
+];
+
+/// Event for rolling a loot table and leaving the results on the floor
 #[derive(Event)]
 pub struct DropLootEvent {
     pub loot_table_id: String,
@@ -12,54 +22,80 @@ pub struct DropLootEvent {
     pub luck: f32,
 }
 
+pub struct LootPlugin;
+
+impl Plugin for LootPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<DropLootEvent>()
+            .add_systems(Update, loot_drop_system);
+    }
+}
+
 pub fn loot_drop_system(
     mut commands: Commands,
     mut events: EventReader<DropLootEvent>,
-    game_data: Res<GameData>,
+    loot_tables: Res<LootTableRegistry>,
+    item_registry: Res<ItemTemplateRegistry>,
+    map: Res<Map>,
+    mut rng: ResMut<GameRng>,
+    mut xp_events: EventWriter<GainExperienceEvent>,
 ) {
     for event in events.read() {
-        let loot_results = game_data.loot_tables.roll_loot(
+        let loot_results = loot_tables.roll_loot(
             &event.loot_table_id,
             event.level,
             event.luck,
+            &mut *rng,
         );
-        
-        for (i, result) in loot_results.iter().enumerate() {
-            let offset_pos = Position {
-                x: event.position.x + (i as i32 % 3) - 1,
-                y: event.position.y + (i as i32 / 3) - 1,
+
+        // Spread the drop over the open tiles around the corpse
+        let spill_tiles: Vec<Position> = SPILL_OFFSETS.iter()
+            .map(|(dx, dy)| Position {
+                x: event.position.x + dx,
+                y: event.position.y + dy,
                 level: event.position.level,
-            };
-            
+            })
+            .filter(|position| map.is_walkable(position))
+            .collect();
+        let mut spill_tiles = spill_tiles.into_iter().cycle();
+
+        for result in loot_results {
             match result {
                 LootResult::Item { item_id, quantity } => {
-                    // Spawn the item
-                    if let Some(mut entity_commands) = spawn_item_from_template(
-                        &mut commands,
-                        &game_data,
-                        item_id,
-                        offset_pos,
-                    ).and_then(|e| commands.get_entity(e)) {
-                        // Update quantity if needed
-                        entity_commands.insert(StackSize(*quantity));
+                    let Some(template) = item_registry.get(&item_id) else {
+                        warn!("Loot table '{}' refers to unknown item '{}'", event.loot_table_id, item_id);
+                        continue;
+                    };
+                    // Drop as few stacks as the item's stack size allows
+                    let mut remaining = quantity;
+                    while remaining > 0 {
+                        let Some(position) = spill_tiles.next() else {
+                            break;
+                        };
+                        let stack = remaining.min(template.max_stack());
+                        spawn_item_from_template(&mut commands, &item_registry, &item_id, position, stack);
+                        remaining -= stack;
                     }
                 }
                 LootResult::Gold { amount } => {
-                    // Spawn gold pile
+                    let Some(position) = spill_tiles.next() else {
+                        continue;
+                    };
                     commands.spawn((
                         Item {
-                            item_type: ItemType::Gold { amount: *amount },
+                            item_type: ItemType::Gold { amount },
                             stack_size: 1,
                         },
                         Name(format!("{} Gold", amount)),
-                        offset_pos,
+                        position,
                     ));
                 }
                 LootResult::Experience { amount } => {
-                    // Handle experience (usually added directly to player)
-                    info!("Dropped {} experience", amount);
+                    // Experience isn't something that can lie on the floor, so it's granted at once
+                    xp_events.send(GainExperienceEvent { amount });
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
@@ -1,19 +1,24 @@
 // src/systems/mod.rs
-// This module will contain various game systems
-// For now, it's a placeholder
+// Gameplay systems that aren't tied to a single plugin
 
 use bevy::prelude::*;
 
-// Future modules will be added here:
-// pub mod item_spawning;
-// pub mod loot_system;
-// pub mod dungeon_generation;
+pub mod consumable_effects;
+pub mod item_spawning;
+pub mod loot_system;
+pub mod monster_spawning;
+pub mod npc_spawning;
+pub mod status_effects;
 
-// Placeholder for systems that aren't part of the plugin architecture yet
 pub struct SystemsPlugin;
 
 impl Plugin for SystemsPlugin {
     fn build(&self, app: &mut App) {
-        // Systems will be registered here
+        app.add_plugins((
+            monster_spawning::MonsterSpawningPlugin,
+            loot_system::LootPlugin,
+            consumable_effects::ConsumableEffectsPlugin,
+            status_effects::StatusEffectsPlugin,
+        ));
     }
-}
\ No newline at end of file
+}
@@ -1,9 +1,14 @@
 // src/systems/monster_spawning.rs
 use bevy::prelude::*;
 use rand::prelude::*;
-use crate::components::{Position, Monster};
+use crate::components::Position;
+use crate::plugins::map::LevelMap;
+use crate::resources::GameRng;
 use crate::templates::monster_templates::{MonsterTemplateRegistry, spawn_monster_from_template};
 
+/// How many levels above the local norm an elite monster spawns at
+const ELITE_LEVEL_BONUS: i32 = 2;
+
 /// Event for requesting a monster spawn
 #[derive(Event)]
 pub struct SpawnMonsterEvent {
@@ -27,14 +32,16 @@ pub fn monster_spawn_system(
     mut commands: Commands,
     mut spawn_events: EventReader<SpawnMonsterEvent>,
     registry: Res<MonsterTemplateRegistry>,
+    mut rng: ResMut<GameRng>,
 ) {
     for event in spawn_events.read() {
         spawn_monster_from_template(
             &mut commands,
             &registry,
             &event.template_id,
-            event.position.clone(),
+            event.position,
             event.level,
+            &mut *rng,
         );
     }
 }
@@ -45,20 +52,17 @@ pub fn random_monster_spawn_system(
     mut spawn_events: EventReader<SpawnRandomMonstersEvent>,
     registry: Res<MonsterTemplateRegistry>,
     existing_positions: Query<&Position>,
+    mut rng: ResMut<GameRng>,
 ) {
-    let mut rng = thread_rng();
-    
+
     for event in spawn_events.read() {
         // Get all valid templates for this spawn event
         let valid_templates: Vec<&str> = if let Some(filter) = &event.template_filter {
             filter.iter().map(|s| s.as_str()).collect()
         } else {
             // Get all templates that can spawn in the requested level range
-            registry.templates.values()
-                .filter(|t| {
-                    t.level_range.0 <= event.level_range.1 && 
-                    t.level_range.1 >= event.level_range.0
-                })
+            registry.get_templates_for_level_range(event.level_range.0, event.level_range.1)
+                .iter()
                 .map(|t| t.id.as_str())
                 .collect()
         };
@@ -91,7 +95,7 @@ pub fn random_monster_spawn_system(
             }
             
             // Select random template
-            let template_id = valid_templates.choose(&mut rng).unwrap();
+            let template_id = valid_templates.choose(&mut *rng).unwrap();
             
             // Generate level within the event's range
             let monster_level = if event.level_range.0 == event.level_range.1 {
@@ -107,6 +111,7 @@ pub fn random_monster_spawn_system(
                 template_id,
                 Position { x, y, level },
                 Some(monster_level),
+                &mut *rng,
             );
             
             spawned += 1;
@@ -123,15 +128,80 @@ pub fn get_appropriate_monsters_for_level(
     registry: &MonsterTemplateRegistry,
     dungeon_level: i32,
 ) -> Vec<String> {
-    registry.templates.values()
-        .filter(|template| {
-            dungeon_level >= template.level_range.0 && 
-            dungeon_level <= template.level_range.1
-        })
+    registry.get_templates_for_level_range(dungeon_level, dungeon_level)
+        .iter()
         .map(|template| template.id.clone())
         .collect()
 }
 
+/// The monster level that matches a dungeon depth. Depth 0 is the first floor,
+/// while monster levels start at 1.
+pub fn monster_level_for_depth(depth: i32) -> i32 {
+    depth + 1
+}
+
+/// Fills a freshly generated level with monsters suited to its depth. Rooms listed
+/// in `skip_rooms` (where the player arrives) are left empty.
+pub fn populate_level<R: Rng + ?Sized>(
+    commands: &mut Commands,
+    registry: &MonsterTemplateRegistry,
+    density: &MonsterDensityConfig,
+    level_map: &LevelMap,
+    depth: i32,
+    skip_rooms: usize,
+    rng: &mut R,
+) {
+    let monster_level = monster_level_for_depth(depth);
+    let candidates = get_appropriate_monsters_for_level(registry, monster_level);
+    if candidates.is_empty() {
+        warn!("No monster templates available for level {}", monster_level);
+        return;
+    }
+
+    let per_room = density.monsters_per_room_base + density.monsters_per_room_per_level * depth as f32;
+
+    for room in level_map.rooms.iter().skip(skip_rooms) {
+        // The fractional part of the density is the chance of one extra group
+        let mut groups = per_room.floor() as usize;
+        if rng.gen::<f32>() < per_room.fract() {
+            groups += 1;
+        }
+
+        let mut free_tiles = level_map.floor_tiles_in(room);
+        free_tiles.shuffle(rng);
+
+        for _ in 0..groups {
+            let Some(template_id) = candidates.choose(rng) else {
+                break;
+            };
+            let group_size = if rng.gen::<f32>() < density.pack_spawn_chance {
+                rng.gen_range(density.pack_size_min..=density.pack_size_max)
+            } else {
+                1
+            };
+
+            for _ in 0..group_size {
+                let Some((x, y)) = free_tiles.pop() else {
+                    break;
+                };
+                let level = if rng.gen::<f32>() < density.elite_chance {
+                    monster_level + ELITE_LEVEL_BONUS
+                } else {
+                    monster_level
+                };
+                spawn_monster_from_template(
+                    commands,
+                    registry,
+                    template_id,
+                    Position { x, y, level: depth },
+                    Some(level),
+                    rng,
+                );
+            }
+        }
+    }
+}
+
 /// Monster density configuration
 #[derive(Resource)]
 pub struct MonsterDensityConfig {
@@ -146,17 +216,16 @@ pub struct MonsterDensityConfig {
 impl Default for MonsterDensityConfig {
     fn default() -> Self {
         Self {
-            monsters_per_room_base: 1.5,
-            monsters_per_room_per_level: 0.2,
+            monsters_per_room_base: 0.3,
+            monsters_per_room_per_level: 0.1,
             elite_chance: 0.1,
-            pack_spawn_chance: 0.3,
+            pack_spawn_chance: 0.2,
             pack_size_min: 2,
-            pack_size_max: 5,
+            pack_size_max: 3,
         }
     }
 }
 
-// Add to your plugin
 pub struct MonsterSpawningPlugin;
 
 impl Plugin for MonsterSpawningPlugin {
@@ -170,4 +239,4 @@ impl Plugin for MonsterSpawningPlugin {
                 random_monster_spawn_system,
             ));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,39 @@
+// src/systems/npc_spawning.rs
+use bevy::prelude::*;
+use rand::prelude::*;
+use crate::components::Position;
+use crate::plugins::map::LevelMap;
+use crate::templates::npc_templates::{spawn_npc_from_template, NPCTemplateRegistry};
+
+/// Places the NPCs who live on this depth in the arrival room, which monsters
+/// never start in. The room's centre is left free for the player and the stairs.
+pub fn populate_npcs<R: Rng + ?Sized>(
+    commands: &mut Commands,
+    registry: &NPCTemplateRegistry,
+    level_map: &LevelMap,
+    depth: i32,
+    rng: &mut R,
+) {
+    let residents = registry.get_templates_for_depth(depth);
+    let Some(room) = level_map.rooms.first() else {
+        return;
+    };
+    if residents.is_empty() {
+        return;
+    }
+
+    let center = room.center();
+    let mut free_tiles: Vec<(i32, i32)> = level_map.floor_tiles_in(room)
+        .into_iter()
+        .filter(|&tile| tile != center)
+        .collect();
+    free_tiles.shuffle(rng);
+
+    for template in residents {
+        let Some((x, y)) = free_tiles.pop() else {
+            warn!("No room left on depth {} for {}", depth, template.name);
+            break;
+        };
+        spawn_npc_from_template(commands, registry, &template.id, Position { x, y, level: depth });
+    }
+}
@@ -0,0 +1,189 @@
+// src/systems/status_effects.rs
+use bevy::prelude::*;
+use std::collections::HashMap;
+use crate::components::{Health, Name, Player, StatusApplied, StatusEffect, StatusEffects, StatusKind};
+use crate::game_state::GameState;
+use crate::plugins::combat::{handle_monster_deaths, process_attacks, CurrentCombat, MonsterSlainEvent};
+use crate::plugins::faction::resolve_feud_attacks;
+use crate::plugins::magic::cast_spell_system;
+use crate::resources::{GameWorld, MessageLog};
+
+/// Event for putting a status effect on someone, from a potion, a venomous bite
+/// or anything else
+#[derive(Event)]
+pub struct ApplyStatusEvent {
+    pub target: Entity,
+    /// What caused it, e.g. "Goblin Archer" or "Elixir of Strength"
+    pub source: String,
+    pub kind: StatusKind,
+    /// In turns
+    pub duration: u32,
+}
+
+pub struct StatusEffectsPlugin;
+
+impl Plugin for StatusEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<ApplyStatusEvent>()
+            // In every state, so effects keep ticking in fights and can be inflicted there
+            .add_systems(
+                Update,
+                (apply_status_system, tick_status_effects)
+                    .chain()
+                    // After this frame's blows and spells, so whatever they killed isn't
+                    // ticked again, and before the dead are cleared away
+                    .after(process_attacks)
+                    .after(cast_spell_system)
+                    .after(resolve_feud_attacks)
+                    .before(handle_monster_deaths)
+                    .run_if(in_state(GameState::Exploring).or_else(in_state(GameState::InCombat)))
+            );
+    }
+}
+
+/// Picks "You" and the second-person verb for the player, the name and the
+/// third-person verb for anyone else
+pub fn describe<'a>(name: &'a str, is_player: bool, verbs: (&'a str, &'a str)) -> (&'a str, &'a str) {
+    if is_player {
+        ("You", verbs.0)
+    } else {
+        (name, verbs.1)
+    }
+}
+
+fn apply_status_system(
+    mut commands: Commands,
+    mut events: EventReader<ApplyStatusEvent>,
+    mut targets: Query<(&Name, &Health, Option<&mut StatusEffects>, Has<Player>)>,
+    game_world: Res<GameWorld>,
+    mut message_log: ResMut<MessageLog>,
+) {
+    // Effects for monsters that had none yet, gathered so several landing at
+    // once don't each insert a component over the last
+    let mut fresh: HashMap<Entity, StatusEffects> = HashMap::new();
+    for event in events.read() {
+        let Ok((name, health, effects, is_player)) = targets.get_mut(event.target) else {
+            continue;
+        };
+        // Whatever brought the effect may have finished the target off already
+        if health.current <= 0 {
+            continue;
+        }
+        let effect = StatusEffect::new(event.kind.clone(), event.source.clone(), event.duration, game_world.turn_count);
+        let applied = match effects {
+            Some(mut effects) => effects.apply(effect),
+            // Monsters only carry status effects once something sticks to them
+            None => fresh.entry(event.target).or_default().apply(effect),
+        };
+
+        let (message, color) = match (&event.kind, applied) {
+            (StatusKind::Poison { .. }, StatusApplied::Stacked(stacks)) => {
+                let (who, verb) = describe(&name.0, is_player, ("feel", "feels"));
+                (format!("{} {} the poison grow stronger (x{}).", who, verb, stacks), Color::GREEN)
+            }
+            (StatusKind::Poison { .. }, _) => {
+                let (who, verb) = describe(&name.0, is_player, ("are", "is"));
+                (format!("{} {} poisoned by {}!", who, verb, event.source), Color::GREEN)
+            }
+            (StatusKind::Regeneration { .. }, _) => {
+                let (who, verb) = describe(&name.0, is_player, ("begin", "begins"));
+                (format!("{} {} to regenerate.", who, verb), Color::LIME_GREEN)
+            }
+            (StatusKind::DetectMonsters { .. }, _) => {
+                let (who, verb) = describe(&name.0, is_player, ("sense", "senses"));
+                (format!("{} {} the creatures around {}.", who, verb, if is_player { "you" } else { "it" }), Color::VIOLET)
+            }
+            (StatusKind::StatBuff { stat, amount }, StatusApplied::Added) => {
+                let (who, verb) = describe(&name.0, is_player, ("feel", "feels"));
+                (format!("{} {} {:+} {} for {} turns.", who, verb, amount, stat, event.duration), Color::CYAN)
+            }
+            (StatusKind::Curse { stat, amount }, StatusApplied::Added) => {
+                let (who, verb) = describe(&name.0, is_player, ("are", "is"));
+                (format!("{} {} cursed by {}! ({} {})", who, verb, event.source, -amount, stat), Color::PURPLE)
+            }
+            (_, _) => {
+                let (who, verb) = describe(&name.0, is_player, ("feel", "feels"));
+                (format!("{} {} the effect of {} renewed.", who, verb, event.source), Color::GRAY)
+            }
+        };
+        message_log.add(message, color);
+    }
+
+    for (target, effects) in fresh {
+        commands.entity(target).insert(effects);
+    }
+}
+
+/// Applies damage and healing over time for every turn that has passed since the
+/// last tick, then drops whatever has worn off
+fn tick_status_effects(
+    mut afflicted: Query<(Entity, &Name, &mut Health, &mut StatusEffects, Has<Player>)>,
+    game_world: Res<GameWorld>,
+    mut message_log: ResMut<MessageLog>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut current_combat: ResMut<CurrentCombat>,
+    mut slain_events: EventWriter<MonsterSlainEvent>,
+) {
+    let turn = game_world.turn_count;
+
+    for (entity, name, mut health, mut effects, is_player) in afflicted.iter_mut() {
+        // Killed earlier this frame and not yet despawned
+        if health.current <= 0 {
+            continue;
+        }
+        // Only touch the component when a turn has passed, so combat stats aren't rederived every frame
+        if !effects.0.iter().any(|effect| effect.last_tick < turn) {
+            continue;
+        }
+
+        let mut poison_damage = 0;
+        let mut regenerated = 0;
+        for effect in effects.0.iter_mut() {
+            let turns = turn.min(effect.expires_at).saturating_sub(effect.last_tick) as i32;
+            effect.last_tick = turn;
+            match effect.kind {
+                StatusKind::Poison { damage } => poison_damage += damage * effect.stacks as i32 * turns,
+                StatusKind::Regeneration { amount } => regenerated += amount * turns,
+                _ => {}
+            }
+        }
+
+        if poison_damage > 0 {
+            health.current -= poison_damage;
+            let (who, verb) = describe(&name.0, is_player, ("take", "takes"));
+            message_log.add(format!("{} {} {} poison damage.", who, verb, poison_damage), Color::GREEN);
+        }
+        // Regeneration can't save someone the poison has already finished off
+        if regenerated > 0 && health.current > 0 {
+            health.current = (health.current + regenerated).min(health.max);
+        }
+
+        effects.0.retain(|effect| {
+            let active = effect.expires_at > turn;
+            if !active && is_player {
+                let message = match effect.kind {
+                    StatusKind::Poison { .. } => "The poison leaves your body.".to_string(),
+                    StatusKind::Regeneration { .. } => "You stop regenerating.".to_string(),
+                    StatusKind::DetectMonsters { .. } => "You no longer sense nearby creatures.".to_string(),
+                    StatusKind::StatBuff { .. } | StatusKind::Curse { .. } => format!("The effect of {} wears off.", effect.source),
+                };
+                message_log.add(message, Color::GRAY);
+            }
+            active
+        });
+
+        if health.current <= 0 {
+            if is_player {
+                message_log.add("You succumb to the poison...".to_string(), Color::RED);
+                next_state.set(GameState::GameOver);
+            } else {
+                // Nobody gets the experience, but whatever it carried still drops
+                message_log.add(format!("{} succumbs to the poison!", name.0), Color::DARK_GRAY);
+                // Out of the turn order at once, so it can't act before it's despawned
+                current_combat.remove(entity);
+                slain_events.send(MonsterSlainEvent { monster: entity, killer: None });
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+// src/templates/dialogue_templates.rs
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A hand-written conversation with one NPC, loaded from `data/dialogue`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogueTree {
+    /// Template ID of the NPC who speaks it
+    pub npc: String,
+    /// Node the conversation opens on
+    pub start: String,
+    pub nodes: Vec<DialogueNode>,
+}
+
+impl DialogueTree {
+    pub fn node(&self, id: &str) -> Option<&DialogueNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+}
+
+/// Something the NPC says, and the replies the player can give
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogueNode {
+    pub id: String,
+    pub text: String,
+    pub responses: Vec<DialogueResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogueResponse {
+    pub text: String,
+    /// Node the NPC answers with; the conversation ends when there isn't one
+    #[serde(default)]
+    pub next: Option<String>,
+    /// All of these have to hold for the reply to be offered
+    #[serde(default)]
+    pub conditions: Vec<DialogueCondition>,
+    /// Applied in order when the reply is chosen
+    #[serde(default)]
+    pub effects: Vec<DialogueEffect>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DialogueCondition {
+    MinLevel(i32),
+    HasGold(u32),
+    /// Carrying at least one of an item, by template ID
+    HasItem(String),
+    /// A dialogue flag has been set
+    Flag(String),
+    NotFlag(String),
+    MinReputation { faction: String, amount: i32 },
+    /// Standing with the faction is no better than `amount`
+    MaxReputation { faction: String, amount: i32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DialogueEffect {
+    GiveGold(u32),
+    TakeGold(u32),
+    /// Puts one of an item, by template ID, in the player's pack
+    GiveItem(String),
+    /// Takes one of an item, by template ID, from the player's pack
+    TakeItem(String),
+    SetFlag(String),
+    ClearFlag(String),
+    ChangeReputation { faction: String, amount: i32 },
+    /// Restores the player's health and mana, as a night's sleep would
+    Rest,
+    /// Opens the speaker's shop, if they keep one
+    OpenShop,
+}
+
+#[derive(Resource, Default)]
+pub struct DialogueRegistry {
+    /// Trees by the template ID of the NPC who speaks them
+    trees: HashMap<String, DialogueTree>,
+}
+
+impl DialogueRegistry {
+    pub fn register(&mut self, tree: DialogueTree) {
+        self.trees.insert(tree.npc.clone(), tree);
+    }
+
+    pub fn get(&self, npc_id: &str) -> Option<&DialogueTree> {
+        self.trees.get(npc_id)
+    }
+
+    pub fn count(&self) -> usize {
+        self.trees.len()
+    }
+
+    pub fn load_from_json(&mut self, json_data: &str) -> Result<(), serde_json::Error> {
+        let trees: Vec<DialogueTree> = serde_json::from_str(json_data)?;
+        for tree in trees {
+            self.register(tree);
+        }
+        Ok(())
+    }
+}
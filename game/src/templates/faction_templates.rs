@@ -0,0 +1,71 @@
+// src/templates/faction_templates.rs
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Factions at or below this opinion of each other are at war, and their
+/// monsters fight on sight
+pub const HOSTILE_RELATIONSHIP: i32 = -50;
+
+/// A group NPCs and monsters can belong to, loaded from `data/factions`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactionTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// What the faction thinks of others, from -100 to 100. Factions not
+    /// listed are neither friend nor foe.
+    #[serde(default)]
+    pub relationships: HashMap<String, i32>,
+}
+
+#[derive(Resource, Default)]
+pub struct FactionRegistry {
+    factions: HashMap<String, FactionTemplate>,
+}
+
+impl FactionRegistry {
+    pub fn register(&mut self, template: FactionTemplate) {
+        self.factions.insert(template.id.clone(), template);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&FactionTemplate> {
+        self.factions.get(id)
+    }
+
+    pub fn count(&self) -> usize {
+        self.factions.len()
+    }
+
+    /// Display name of a faction, falling back to its ID for ones the registry doesn't know
+    pub fn name<'a>(&'a self, id: &'a str) -> &'a str {
+        self.get(id).map_or(id, |faction| faction.name.as_str())
+    }
+
+    /// What `faction` thinks of `other`
+    pub fn relationship(&self, faction: &str, other: &str) -> i32 {
+        self.get(faction)
+            .and_then(|faction| faction.relationships.get(other).copied())
+            .unwrap_or(0)
+    }
+
+    /// Whether either side of a pair of factions is at war with the other
+    pub fn is_hostile(&self, a: &str, b: &str) -> bool {
+        a != b && (self.relationship(a, b) <= HOSTILE_RELATIONSHIP || self.relationship(b, a) <= HOSTILE_RELATIONSHIP)
+    }
+
+    /// Every faction, by ID so anything listing them keeps a stable order
+    pub fn all(&self) -> Vec<&FactionTemplate> {
+        let mut factions: Vec<&FactionTemplate> = self.factions.values().collect();
+        factions.sort_by(|a, b| a.id.cmp(&b.id));
+        factions
+    }
+
+    pub fn load_from_json(&mut self, json_data: &str) -> Result<(), serde_json::Error> {
+        let templates: Vec<FactionTemplate> = serde_json::from_str(json_data)?;
+        for template in templates {
+            self.register(template);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+// src/templates/item_templates.rs
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::components::EquipSlot;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ItemTemplate {
+    Weapon(WeaponTemplate),
+    Armor(ArmorTemplate),
+    Consumable(ConsumableTemplate),
+    Misc(MiscItemTemplate),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeaponTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub weapon_type: WeaponType,
+    pub damage: DamageRange,
+    pub attack_speed: f32,
+    pub requirements: ItemRequirements,
+    pub modifiers: Vec<StatModifier>,
+    pub rarity: ItemRarity,
+    pub value: u32,
+    pub stack_size: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArmorTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub armor_type: ArmorType,
+    pub defense: i32,
+    pub requirements: ItemRequirements,
+    pub modifiers: Vec<StatModifier>,
+    pub rarity: ItemRarity,
+    pub value: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsumableTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub consumable_type: ConsumableType,
+    pub effects: Vec<ConsumableEffect>,
+    pub charges: u32,
+    pub cooldown: f32,
+    pub value: u32,
+    pub stack_size: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MiscItemTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    pub value: u32,
+    pub stack_size: u32,
+    pub quest_item: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DamageRange {
+    pub min: i32,
+    pub max: i32,
+    pub damage_type: DamageType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WeaponType {
+    Sword,
+    Axe,
+    Mace,
+    Dagger,
+    Staff,
+    Bow,
+    Crossbow,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ArmorType {
+    Light,
+    Medium,
+    Heavy,
+    Shield,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DamageType {
+    Physical,
+    Fire,
+    Cold,
+    Lightning,
+    Poison,
+    Holy,
+    Shadow,
+}
+
+impl DamageType {
+    pub const ALL: [DamageType; 7] = [
+        DamageType::Physical,
+        DamageType::Fire,
+        DamageType::Cold,
+        DamageType::Lightning,
+        DamageType::Poison,
+        DamageType::Holy,
+        DamageType::Shadow,
+    ];
+
+    /// Lowercase name used in the message log
+    pub fn name(&self) -> &'static str {
+        match self {
+            DamageType::Physical => "physical",
+            DamageType::Fire => "fire",
+            DamageType::Cold => "cold",
+            DamageType::Lightning => "lightning",
+            DamageType::Poison => "poison",
+            DamageType::Holy => "holy",
+            DamageType::Shadow => "shadow",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ConsumableType {
+    Potion,
+    Scroll,
+    Food,
+    Elixir,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ConsumableEffect {
+    Heal { amount: i32 },
+    RestoreMana { amount: i32 },
+    Buff { stat: String, amount: i32, duration: f32 },
+    /// Heals `amount` every turn for `duration` turns
+    Regenerate { amount: i32, duration: f32 },
+    CurePoison,
+    RemoveCurse,
+    Teleport,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemRequirements {
+    pub level: Option<u32>,
+    pub strength: Option<i32>,
+    pub dexterity: Option<i32>,
+    pub intelligence: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatModifier {
+    pub stat: String,
+    pub modifier_type: ModifierType,
+    pub value: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ModifierType {
+    Flat,
+    Percentage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ItemRarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl ItemTemplate {
+    pub fn id(&self) -> &str {
+        match self {
+            ItemTemplate::Weapon(w) => &w.id,
+            ItemTemplate::Armor(a) => &a.id,
+            ItemTemplate::Consumable(c) => &c.id,
+            ItemTemplate::Misc(m) => &m.id,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            ItemTemplate::Weapon(w) => &w.name,
+            ItemTemplate::Armor(a) => &a.name,
+            ItemTemplate::Consumable(c) => &c.name,
+            ItemTemplate::Misc(m) => &m.name,
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            ItemTemplate::Weapon(w) => &w.description,
+            ItemTemplate::Armor(a) => &a.description,
+            ItemTemplate::Consumable(c) => &c.description,
+            ItemTemplate::Misc(m) => &m.description,
+        }
+    }
+
+    /// Base price in gold
+    pub fn value(&self) -> u32 {
+        match self {
+            ItemTemplate::Weapon(w) => w.value,
+            ItemTemplate::Armor(a) => a.value,
+            ItemTemplate::Consumable(c) => c.value,
+            ItemTemplate::Misc(m) => m.value,
+        }
+    }
+
+    /// Consumables and misc items don't have a rarity and count as common
+    pub fn rarity(&self) -> ItemRarity {
+        match self {
+            ItemTemplate::Weapon(w) => w.rarity.clone(),
+            ItemTemplate::Armor(a) => a.rarity.clone(),
+            ItemTemplate::Consumable(_) | ItemTemplate::Misc(_) => ItemRarity::Common,
+        }
+    }
+
+    /// How many of this item fit in one inventory slot. Armor never stacks.
+    pub fn max_stack(&self) -> u32 {
+        let stack_size = match self {
+            ItemTemplate::Weapon(w) => w.stack_size,
+            ItemTemplate::Armor(_) => 1,
+            ItemTemplate::Consumable(c) => c.stack_size,
+            ItemTemplate::Misc(m) => m.stack_size,
+        };
+        stack_size.max(1)
+    }
+
+    /// Where the item is worn, for weapons and armor
+    pub fn equip_slot(&self) -> Option<EquipSlot> {
+        match self {
+            ItemTemplate::Weapon(_) => Some(EquipSlot::Weapon),
+            ItemTemplate::Armor(a) if matches!(a.armor_type, ArmorType::Shield) => Some(EquipSlot::Shield),
+            ItemTemplate::Armor(_) => Some(EquipSlot::Armor),
+            ItemTemplate::Consumable(_) | ItemTemplate::Misc(_) => None,
+        }
+    }
+
+    pub fn requirements(&self) -> Option<&ItemRequirements> {
+        match self {
+            ItemTemplate::Weapon(w) => Some(&w.requirements),
+            ItemTemplate::Armor(a) => Some(&a.requirements),
+            ItemTemplate::Consumable(_) | ItemTemplate::Misc(_) => None,
+        }
+    }
+
+    pub fn modifiers(&self) -> &[StatModifier] {
+        match self {
+            ItemTemplate::Weapon(w) => &w.modifiers,
+            ItemTemplate::Armor(a) => &a.modifiers,
+            ItemTemplate::Consumable(_) | ItemTemplate::Misc(_) => &[],
+        }
+    }
+
+    /// Character level needed to use the item, if it has one
+    pub fn required_level(&self) -> Option<u32> {
+        match self {
+            ItemTemplate::Weapon(w) => w.requirements.level,
+            ItemTemplate::Armor(a) => a.requirements.level,
+            ItemTemplate::Consumable(_) | ItemTemplate::Misc(_) => None,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ItemTemplateRegistry {
+    items: HashMap<String, ItemTemplate>,
+}
+
+impl ItemTemplateRegistry {
+    pub fn register(&mut self, template: ItemTemplate) {
+        self.items.insert(template.id().to_string(), template);
+    }
+    
+    pub fn get(&self, id: &str) -> Option<&ItemTemplate> {
+        self.items.get(id)
+    }
+
+    /// Templates that can turn up on the floor at the given character level,
+    /// ordered by ID so seeded rolls over the result are reproducible
+    pub fn get_templates_up_to_level(&self, level: i32) -> Vec<&ItemTemplate> {
+        let mut templates: Vec<&ItemTemplate> = self.items.values()
+            .filter(|template| template.required_level().is_none_or(|required| required as i32 <= level))
+            .collect();
+        templates.sort_by(|a, b| a.id().cmp(b.id()));
+        templates
+    }
+    
+    pub fn count(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn load_from_json(&mut self, json_data: &str) -> Result<(), serde_json::Error> {
+        let templates: Vec<ItemTemplate> = serde_json::from_str(json_data)?;
+        for template in templates {
+            self.register(template);
+        }
+        Ok(())
+    }
+}
+
+/// Component that references which template an item was created from
+#[derive(Component)]
+pub struct ItemTemplateRef(pub String);
@@ -0,0 +1,214 @@
+// src/templates/loot_tables.rs
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LootTable {
+    pub id: String,
+    pub name: String,
+    pub rolls: LootRolls,
+    pub entries: Vec<LootEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LootRolls {
+    pub min: u32,
+    pub max: u32,
+    pub bonus_rolls: Option<BonusRolls>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BonusRolls {
+    pub per_level: f32,
+    pub per_luck: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LootEntry {
+    pub weight: f32,
+    pub item: LootItem,
+    pub conditions: Vec<LootCondition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum LootItem {
+    Item { 
+        id: String, 
+        quantity: QuantityRange,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        modifiers: Option<Vec<String>>,
+    },
+    Table { 
+        id: String 
+    },
+    Gold { 
+        amount: QuantityRange 
+    },
+    Experience { 
+        amount: QuantityRange 
+    },
+    Nothing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantityRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum LootCondition {
+    MinLevel { level: u32 },
+    MaxLevel { level: u32 },
+    Random { chance: f32 },
+    KilledByElement { element: String },
+    PlayerHasItem { item_id: String },
+}
+
+/// Nested tables deeper than this are ignored, as a backstop against runaway recursion
+const MAX_TABLE_DEPTH: usize = 8;
+
+#[derive(Resource, Default)]
+pub struct LootTableRegistry {
+    tables: HashMap<String, LootTable>,
+}
+
+impl LootTableRegistry {
+    pub fn register(&mut self, table: LootTable) {
+        self.tables.insert(table.id.clone(), table);
+    }
+    
+    pub fn get(&self, id: &str) -> Option<&LootTable> {
+        self.tables.get(id)
+    }
+    
+    pub fn count(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn load_from_json(&mut self, json_data: &str) -> Result<(), serde_json::Error> {
+        let tables: Vec<LootTable> = serde_json::from_str(json_data)?;
+        for table in tables {
+            self.register(table);
+        }
+        Ok(())
+    }
+    
+    pub fn roll_loot<R: Rng + ?Sized>(&self, table_id: &str, level: u32, luck: f32, rng: &mut R) -> Vec<LootResult> {
+        let mut results = Vec::new();
+        let mut visiting = Vec::new();
+        self.roll_table(table_id, level, luck, rng, &mut visiting, &mut results);
+        results
+    }
+
+    /// Rolls one table into `results`. `visiting` holds the chain of tables currently
+    /// being rolled, so a table that refers back to one of its parents is skipped
+    /// instead of recursing forever.
+    fn roll_table<'a, R: Rng + ?Sized>(
+        &'a self,
+        table_id: &'a str,
+        level: u32,
+        luck: f32,
+        rng: &mut R,
+        visiting: &mut Vec<&'a str>,
+        results: &mut Vec<LootResult>,
+    ) {
+        if visiting.contains(&table_id) {
+            warn!("Loot table '{}' refers back to itself via {:?}; skipping", table_id, visiting);
+            return;
+        }
+        if visiting.len() >= MAX_TABLE_DEPTH {
+            warn!("Loot tables nested too deeply at '{}'; skipping", table_id);
+            return;
+        }
+        let Some(table) = self.get(table_id) else {
+            warn!("Unknown loot table '{}'", table_id);
+            return;
+        };
+        visiting.push(table_id);
+
+        // Calculate number of rolls
+        let base_rolls = roll_range(table.rolls.min, table.rolls.max, rng);
+        let bonus_rolls = if let Some(bonus) = &table.rolls.bonus_rolls {
+            let level_bonus = (level as f32 * bonus.per_level) as u32;
+            let luck_bonus = (luck * bonus.per_luck).max(0.0) as u32;
+            level_bonus + luck_bonus
+        } else {
+            0
+        };
+
+        // Perform rolls
+        for _ in 0..base_rolls + bonus_rolls {
+            let Some(entry) = self.weighted_random_select(&table.entries, level, rng) else {
+                continue;
+            };
+            match &entry.item {
+                LootItem::Item { id, quantity, .. } => {
+                    results.push(LootResult::Item {
+                        item_id: id.clone(),
+                        quantity: roll_range(quantity.min, quantity.max, rng),
+                    });
+                }
+                LootItem::Table { id } => {
+                    self.roll_table(id, level, luck, rng, visiting, results);
+                }
+                LootItem::Gold { amount } => {
+                    results.push(LootResult::Gold { amount: roll_range(amount.min, amount.max, rng) });
+                }
+                LootItem::Experience { amount } => {
+                    results.push(LootResult::Experience { amount: roll_range(amount.min, amount.max, rng) });
+                }
+                LootItem::Nothing => {}
+            }
+        }
+
+        visiting.pop();
+    }
+    
+    fn weighted_random_select<'a, R: Rng + ?Sized>(&self, entries: &'a [LootEntry], level: u32, rng: &mut R) -> Option<&'a LootEntry> {
+        let eligible_entries: Vec<(&LootEntry, f32)> = entries.iter()
+            .filter(|entry| {
+                // Check conditions
+                entry.conditions.iter().all(|condition| match condition {
+                    LootCondition::MinLevel { level: min } => level >= *min,
+                    LootCondition::MaxLevel { level: max } => level <= *max,
+                    LootCondition::Random { chance } => rng.gen::<f32>() <= *chance,
+                    _ => true, // Other conditions need more context
+                })
+            })
+            .map(|entry| (entry, entry.weight))
+            .collect();
+        
+        if eligible_entries.is_empty() {
+            return None;
+        }
+        
+        let total_weight: f32 = eligible_entries.iter().map(|(_, w)| w).sum();
+        let mut roll = rng.gen::<f32>() * total_weight;
+        
+        for (entry, weight) in eligible_entries {
+            roll -= weight;
+            if roll <= 0.0 {
+                return Some(entry);
+            }
+        }
+        
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LootResult {
+    Item { item_id: String, quantity: u32 },
+    Gold { amount: u32 },
+    Experience { amount: u32 },
+}
+
+/// Uniform roll over an inclusive range, tolerating tables authored with min > max
+fn roll_range<R: Rng + ?Sized>(min: u32, max: u32, rng: &mut R) -> u32 {
+    rng.gen_range(min.min(max)..=min.max(max))
+}
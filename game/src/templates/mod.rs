@@ -1,2 +1,9 @@
 // src/templates/mod.rs
+pub mod dialogue_templates;
+pub mod faction_templates;
+pub mod item_templates;
+pub mod loot_tables;
 pub mod monster_templates;
+pub mod npc_templates;
+pub mod quest_templates;
+pub mod spell_templates;
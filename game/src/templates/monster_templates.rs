@@ -1,8 +1,11 @@
 // src/templates/monster_templates.rs
 use bevy::prelude::*;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use crate::components::{AIType, CombatStats, Health, Stats, Monster, Name, Position};
+use crate::components::{AIType, CombatStats, Health, Level, Stats, StatusKind, Monster, Name, Position, Viewshed};
+use crate::plugins::map::MONSTER_VIEW_RANGE;
+use crate::templates::item_templates::DamageType;
 
 /// A template that defines the base properties for a type of monster
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,12 +43,36 @@ pub struct MonsterTemplate {
     
     /// Loot table reference (for future implementation)
     pub loot_table_id: Option<String>,
+
+    /// Faction the monster fights for. Monsters of factions at war with each
+    /// other fight whenever they meet.
+    #[serde(default)]
+    pub faction: Option<String>,
+
+    /// Damage multipliers by type, overriding the family defaults
+    /// (0.0 immune, below 1.0 resistant, above 1.0 weak)
+    #[serde(default)]
+    pub resistances: HashMap<DamageType, f32>,
+
+    /// Status effects the monster's hits can inflict, like venom or hexes
+    #[serde(default)]
+    pub on_hit: Vec<OnHitEffect>,
     
     /// Visual representation
     pub display_char: char,
     pub display_color: [f32; 3],
 }
 
+/// A status effect a monster's hits have a chance of inflicting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnHitEffect {
+    pub status: StatusKind,
+    /// In turns
+    pub duration: u32,
+    /// From 0.0 to 1.0
+    pub chance: f64,
+}
+
 /// Monster families for categorization
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum MonsterFamily {
@@ -59,6 +86,46 @@ pub enum MonsterFamily {
     Aberration,
 }
 
+impl MonsterFamily {
+    /// How much damage of a type members of this family take, before any
+    /// per-template overrides
+    pub fn default_multiplier(&self, damage_type: DamageType) -> f32 {
+        match (self, damage_type) {
+            (MonsterFamily::Undead, DamageType::Holy) => 2.0,
+            (MonsterFamily::Undead, DamageType::Poison) => 0.0,
+            (MonsterFamily::Undead, DamageType::Shadow) => 0.5,
+            (MonsterFamily::Elemental, DamageType::Poison) => 0.0,
+            (MonsterFamily::Demon, DamageType::Holy) => 1.5,
+            (MonsterFamily::Demon, DamageType::Fire) => 0.5,
+            (MonsterFamily::Dragon, DamageType::Fire) => 0.5,
+            (MonsterFamily::Construct, DamageType::Poison) => 0.0,
+            (MonsterFamily::Construct, DamageType::Lightning) => 1.5,
+            (MonsterFamily::Beast, DamageType::Fire) => 1.25,
+            _ => 1.0,
+        }
+    }
+}
+
+impl MonsterTemplate {
+    /// The multiplier applied to damage of a type dealt to this monster
+    pub fn damage_multiplier(&self, damage_type: DamageType) -> f32 {
+        self.resistances
+            .get(&damage_type)
+            .copied()
+            .unwrap_or_else(|| self.family.default_multiplier(damage_type))
+    }
+
+    /// Combat stats of the monster at a level, before any status effects
+    pub fn combat_stats_at(&self, level: i32) -> CombatStats {
+        CombatStats {
+            damage: self.combat.base_damage + ((self.combat.damage_per_level * (level - 1) as f32) as i32),
+            defense: self.combat.base_defense + ((self.combat.defense_per_level * (level - 1) as f32) as i32),
+            accuracy: self.combat.base_accuracy,
+            evasion: self.combat.base_evasion,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthTemplate {
     pub base_health: i32,
@@ -131,13 +198,16 @@ impl MonsterTemplateRegistry {
         self.templates.get(id)
     }
     
-    /// Get all templates that can spawn within the given level range
+    /// Get all templates that can spawn within the given level range, ordered by ID
+    /// so seeded rolls over the result are reproducible
     pub fn get_templates_for_level_range(&self, min_level: i32, max_level: i32) -> Vec<&MonsterTemplate> {
-        self.templates.values()
+        let mut templates: Vec<&MonsterTemplate> = self.templates.values()
             .filter(|template| {
                 template.level_range.0 <= max_level && template.level_range.1 >= min_level
             })
-            .collect()
+            .collect();
+        templates.sort_by(|a, b| a.id.cmp(&b.id));
+        templates
     }
     
     /// Get all templates of a specific monster type
@@ -175,12 +245,12 @@ impl MonsterTemplateRegistry {
     }
     
     /// Get a random template of a specific type within level range
-    pub fn get_random_template_by_type(&self, monster_type: &str, min_level: i32, max_level: i32) -> Option<&MonsterTemplate> {
+    pub fn get_random_template_by_type<R: Rng + ?Sized>(&self, monster_type: &str, min_level: i32, max_level: i32, rng: &mut R) -> Option<&MonsterTemplate> {
         let candidates = self.get_templates_by_type_and_level(monster_type, min_level, max_level);
         if candidates.is_empty() {
             None
         } else {
-            let index = rand::random::<usize>() % candidates.len();
+            let index = rng.gen::<usize>() % candidates.len();
             Some(candidates[index])
         }
     }
@@ -202,23 +272,18 @@ impl MonsterTemplateRegistry {
 }
 
 /// System for spawning monsters from templates
-pub fn spawn_monster_from_template(
+pub fn spawn_monster_from_template<R: Rng + ?Sized>(
     commands: &mut Commands,
     registry: &MonsterTemplateRegistry,
     template_id: &str,
     position: Position,
     level: Option<i32>,
+    rng: &mut R,
 ) -> Option<Entity> {
     let template = registry.get(template_id)?;
     
     // Calculate the monster's level
-    let monster_level = level.unwrap_or_else(|| {
-        if template.level_range.0 == template.level_range.1 {
-            template.level_range.0
-        } else {
-            rand::random::<i32>().abs() % (template.level_range.1 - template.level_range.0 + 1) + template.level_range.0
-        }
-    });
+    let monster_level = level.unwrap_or_else(|| rng.gen_range(template.level_range.0..=template.level_range.1));
     
     // Calculate scaled stats
     let health = Health {
@@ -233,20 +298,17 @@ pub fn spawn_monster_from_template(
         constitution: template.stats.base_constitution + ((template.stats.constitution_per_level * (monster_level - 1) as f32) as i32),
     };
     
-    let combat_stats = CombatStats {
-        damage: template.combat.base_damage + ((template.combat.damage_per_level * (monster_level - 1) as f32) as i32),
-        defense: template.combat.base_defense + ((template.combat.defense_per_level * (monster_level - 1) as f32) as i32),
-        accuracy: template.combat.base_accuracy,
-        evasion: template.combat.base_evasion,
-    };
+    let combat_stats = template.combat_stats_at(monster_level);
     
     let entity = commands.spawn((
         Monster { ai_type: template.ai_type.clone() },
         Name(format!("{} (Lv.{})", template.name, monster_level)),
+        Level(monster_level),
         position,
         health,
         stats,
         combat_stats,
+        Viewshed::new(MONSTER_VIEW_RANGE),
         // Add a component to track the template this monster came from
         MonsterTemplateRef(template.id.clone()),
     )).id();
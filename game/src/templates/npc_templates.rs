@@ -0,0 +1,188 @@
+// src/templates/npc_templates.rs
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::components::{CombatStats, Health, Level, Name, Npc, Position, Stats};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NPCTemplate {
+    pub id: String,
+    pub name: String,
+    pub title: Option<String>,
+    pub description: String,
+    pub npc_type: NPCType,
+    pub dialogue_personality: DialoguePersonality,
+    pub services: Vec<NPCService>,
+    pub faction: Option<String>,
+    pub importance: NPCImportance,
+    pub spawn_locations: Vec<String>,
+}
+
+impl NPCTemplate {
+    /// The loot table the NPC's shop is stocked from, if they keep one
+    pub fn shop_table(&self) -> Option<&str> {
+        self.services.iter().find_map(|service| match service {
+            NPCService::Shop { inventory_table } => Some(inventory_table.as_str()),
+            _ => None,
+        })
+    }
+
+    /// The quests the NPC hands out, if any
+    pub fn quest_ids(&self) -> &[String] {
+        self.services.iter()
+            .find_map(|service| match service {
+                NPCService::Quest { quest_ids } => Some(quest_ids.as_slice()),
+                _ => None,
+            })
+            .unwrap_or(&[])
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NPCType {
+    Merchant,
+    QuestGiver,
+    Trainer,
+    Guard,
+    Civilian,
+    Noble,
+    Innkeeper,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialoguePersonality {
+    pub tone: String,           // "friendly", "gruff", "mysterious", etc.
+    pub speaking_style: String, // "formal", "casual", "archaic", etc.
+    pub interests: Vec<String>, // Topics they like to discuss
+    pub knowledge_areas: Vec<String>, // What they know about
+    pub personality_traits: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NPCService {
+    Shop { inventory_table: String },
+    Quest { quest_ids: Vec<String> },
+    Training { skills: Vec<String> },
+    Inn { room_cost: u32 },
+    Crafting { craft_types: Vec<String> },
+}
+
+impl NPCService {
+    /// How the service is offered in the NPC's dialog
+    pub fn label(&self) -> String {
+        match self {
+            NPCService::Shop { .. } => "Trade".to_string(),
+            NPCService::Quest { quest_ids } => format!("Quests ({})", quest_ids.len()),
+            NPCService::Training { skills } => format!("Training: {}", skills.join(", ")),
+            NPCService::Inn { room_cost } => format!("A room for the night ({} gold)", room_cost),
+            NPCService::Crafting { craft_types } => format!("Crafting: {}", craft_types.join(", ")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NPCImportance {
+    Essential,  // Cannot be killed
+    Important,  // Warns player before attacking
+    Normal,     // Regular NPC
+}
+
+/// A named place NPCs can live, e.g. "town_square", and the dungeon depth it's found on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NPCLocation {
+    pub id: String,
+    pub depth: i32,
+}
+
+#[derive(Resource, Default)]
+pub struct NPCTemplateRegistry {
+    npcs: HashMap<String, NPCTemplate>,
+    locations: HashMap<String, i32>,
+}
+
+impl NPCTemplateRegistry {
+    pub fn register(&mut self, template: NPCTemplate) {
+        self.npcs.insert(template.id.clone(), template);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&NPCTemplate> {
+        self.npcs.get(id)
+    }
+
+    pub fn count(&self) -> usize {
+        self.npcs.len()
+    }
+
+    pub fn register_location(&mut self, location: NPCLocation) {
+        self.locations.insert(location.id, location.depth);
+    }
+
+    /// The depth an NPC lives on: that of the first of its spawn locations the
+    /// dungeon knows about. NPCs are unique, so they never appear anywhere else.
+    pub fn home_depth(&self, template: &NPCTemplate) -> Option<i32> {
+        template.spawn_locations.iter()
+            .find_map(|location| self.locations.get(location).copied())
+    }
+
+    /// NPCs living on a depth, by ID so spawning stays the same for a seed
+    pub fn get_templates_for_depth(&self, depth: i32) -> Vec<&NPCTemplate> {
+        let mut templates: Vec<&NPCTemplate> = self.npcs.values()
+            .filter(|template| self.home_depth(template) == Some(depth))
+            .collect();
+        templates.sort_by(|a, b| a.id.cmp(&b.id));
+        templates
+    }
+
+    pub fn load_from_json(&mut self, json_data: &str) -> Result<(), serde_json::Error> {
+        let templates: Vec<NPCTemplate> = serde_json::from_str(json_data)?;
+        for template in templates {
+            self.register(template);
+        }
+        Ok(())
+    }
+
+    pub fn load_locations_from_json(&mut self, json_data: &str) -> Result<(), serde_json::Error> {
+        let locations: Vec<NPCLocation> = serde_json::from_str(json_data)?;
+        for location in locations {
+            self.register_location(location);
+        }
+        Ok(())
+    }
+}
+
+pub fn spawn_npc_from_template(
+    commands: &mut Commands,
+    registry: &NPCTemplateRegistry,
+    template_id: &str,
+    position: Position,
+) -> Option<Entity> {
+    let template = registry.get(template_id)?;
+
+    // NPCs aren't built for fighting, but guards can hold their own
+    let (level, health, stats, combat_stats) = match template.npc_type {
+        NPCType::Guard => (
+            Level(3),
+            Health { current: 45, max: 45 },
+            Stats { strength: 14, dexterity: 12, intelligence: 8, constitution: 14 },
+            CombatStats { damage: 7, defense: 4, accuracy: 80, evasion: 10 },
+        ),
+        _ => (
+            Level(1),
+            Health { current: 20, max: 20 },
+            Stats { strength: 9, dexterity: 9, intelligence: 10, constitution: 9 },
+            CombatStats { damage: 3, defense: 1, accuracy: 65, evasion: 8 },
+        ),
+    };
+
+    let entity = commands.spawn((
+        Npc { template_id: template.id.clone() },
+        Name(template.name.clone()),
+        level,
+        position,
+        health,
+        stats,
+        combat_stats,
+    )).id();
+
+    Some(entity)
+}
@@ -0,0 +1,79 @@
+// src/templates/quest_templates.rs
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// A quest NPCs hand out through `NPCService::Quest`, loaded from `data/quests`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuestTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// All of these have to be done before the quest can be handed in
+    pub objectives: Vec<QuestObjective>,
+    #[serde(default)]
+    pub rewards: QuestRewards,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum QuestObjective {
+    /// Kill monsters of a type, e.g. "Goblin"
+    Kill { monster_type: String, count: u32 },
+    /// Bring items back to the quest giver, who keeps them
+    Fetch { item_id: String, count: u32 },
+    /// Get down to a dungeon depth, counted from 0 like NPC locations
+    ReachDepth { depth: i32 },
+    /// Speak to an NPC, by template ID
+    TalkTo { npc: String },
+}
+
+impl QuestObjective {
+    /// How much progress completes the objective
+    pub fn required(&self) -> u32 {
+        match self {
+            QuestObjective::Kill { count, .. } | QuestObjective::Fetch { count, .. } => *count,
+            QuestObjective::ReachDepth { .. } | QuestObjective::TalkTo { .. } => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuestRewards {
+    #[serde(default)]
+    pub experience: u32,
+    #[serde(default)]
+    pub gold: u32,
+    /// Each table is rolled once at the player's level
+    #[serde(default)]
+    pub loot_tables: Vec<String>,
+    /// Standing gained or lost with factions, by faction ID
+    #[serde(default)]
+    pub reputation: BTreeMap<String, i32>,
+}
+
+#[derive(Resource, Default)]
+pub struct QuestTemplateRegistry {
+    quests: HashMap<String, QuestTemplate>,
+}
+
+impl QuestTemplateRegistry {
+    pub fn register(&mut self, template: QuestTemplate) {
+        self.quests.insert(template.id.clone(), template);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&QuestTemplate> {
+        self.quests.get(id)
+    }
+
+    pub fn count(&self) -> usize {
+        self.quests.len()
+    }
+
+    pub fn load_from_json(&mut self, json_data: &str) -> Result<(), serde_json::Error> {
+        let templates: Vec<QuestTemplate> = serde_json::from_str(json_data)?;
+        for template in templates {
+            self.register(template);
+        }
+        Ok(())
+    }
+}
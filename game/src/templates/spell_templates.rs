@@ -0,0 +1,80 @@
+// src/templates/spell_templates.rs
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::components::StatusKind;
+use crate::templates::item_templates::DamageRange;
+
+/// A spell the player can cast, loaded from `data/spells`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpellTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub mana_cost: i32,
+    /// Character level the spell is learned at
+    pub level: i32,
+    pub target: SpellTarget,
+    /// Applied in order to the spell's target
+    pub effects: Vec<SpellEffect>,
+}
+
+/// Who a spell's effects land on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpellTarget {
+    /// The caster themselves
+    Caster,
+    /// The monster targeted in the current fight; these can only be cast in combat
+    Enemy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SpellEffect {
+    /// Rolls in the range and adds the caster's intelligence modifier. Armour
+    /// doesn't help against spells, but resistances do.
+    Damage { damage: DamageRange },
+    Heal { amount: i32 },
+    /// Inflicts or grants a status effect for `duration` turns
+    Status { status: StatusKind, duration: u32 },
+    /// Reveals the map within `radius` tiles of the target
+    Light { radius: i32 },
+    /// Senses monsters within `radius` tiles through walls for `duration` turns
+    DetectMonsters { radius: i32, duration: u32 },
+}
+
+#[derive(Resource, Default)]
+pub struct SpellTemplateRegistry {
+    spells: HashMap<String, SpellTemplate>,
+}
+
+impl SpellTemplateRegistry {
+    pub fn register(&mut self, template: SpellTemplate) {
+        self.spells.insert(template.id.clone(), template);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&SpellTemplate> {
+        self.spells.get(id)
+    }
+
+    /// Spells known at a character level, lowest level first and then by ID so
+    /// the spellbook keeps a stable order
+    pub fn known_at(&self, level: i32) -> Vec<&SpellTemplate> {
+        let mut spells: Vec<&SpellTemplate> = self.spells.values()
+            .filter(|spell| spell.level <= level)
+            .collect();
+        spells.sort_by(|a, b| a.level.cmp(&b.level).then_with(|| a.id.cmp(&b.id)));
+        spells
+    }
+
+    pub fn count(&self) -> usize {
+        self.spells.len()
+    }
+
+    pub fn load_from_json(&mut self, json_data: &str) -> Result<(), serde_json::Error> {
+        let templates: Vec<SpellTemplate> = serde_json::from_str(json_data)?;
+        for template in templates {
+            self.register(template);
+        }
+        Ok(())
+    }
+}